@@ -12,7 +12,7 @@ pub fn encode(bytes: &[u8]) -> String {
     sextets
         .into_iter()
         .map(encode_single)
-        .chain(padding.into_iter())
+        .chain(padding)
         .collect()
 }
 
@@ -193,7 +193,7 @@ impl OctetBuilder {
         self
     }
 
-    fn bytes(mut self) -> Vec<u8> {
+    fn bytes(self) -> Vec<u8> {
         self.bytes
     }
 }
@@ -230,7 +230,7 @@ mod tests {
             'z', '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '+', '/',
         ];
 
-        let alphabet: Vec<char> = (0..=63).into_iter().map(encode_single).collect();
+        let alphabet: Vec<char> = (0..=63).map(encode_single).collect();
 
         assert_eq!(rfc4648.len(), alphabet.len());
         assert!(rfc4648.iter().zip(alphabet.iter()).all(|(a, b)| a == b));
@@ -240,7 +240,7 @@ mod tests {
 
     #[test]
     fn sextet_iterator_three_bytes() {
-        let sextets: Vec<u8> = SextetIter::from(&BYTES).into_iter().collect();
+        let sextets: Vec<u8> = SextetIter::from(&BYTES).collect();
         let expected: [u8; 4] = [0b01_0011, 0b01_0110, 0b00_0101, 0b10_1110];
 
         assert_eq!(sextets.len(), expected.len());
@@ -249,7 +249,7 @@ mod tests {
 
     #[test]
     fn sextet_iterator_two_bytes() {
-        let sextets: Vec<u8> = SextetIter::from(&BYTES[0..2]).into_iter().collect();
+        let sextets: Vec<u8> = SextetIter::from(&BYTES[0..2]).collect();
         let expected: [u8; 3] = [0b01_0011, 0b01_0110, 0b00_0100];
 
         assert_eq!(sextets.len(), expected.len());
@@ -258,7 +258,7 @@ mod tests {
 
     #[test]
     fn sextet_iterator_one_byte() {
-        let sextets: Vec<u8> = SextetIter::from(&BYTES[0..1]).into_iter().collect();
+        let sextets: Vec<u8> = SextetIter::from(&BYTES[0..1]).collect();
         let expected: [u8; 2] = [0b01_0011, 0b01_0000];
 
         assert_eq!(sextets.len(), expected.len());
@@ -267,7 +267,7 @@ mod tests {
 
     #[test]
     fn sextet_iterator_zero_bytes() {
-        let sextets: Vec<u8> = SextetIter::from(&BYTES[0..0]).into_iter().collect();
+        let sextets: Vec<u8> = SextetIter::from(&BYTES[0..0]).collect();
         let expected: [u8; 0] = [];
 
         assert_eq!(sextets.len(), expected.len());
@@ -0,0 +1,68 @@
+use crate::{Buffer, EncryptionOracle};
+
+/// The cipher block size of `oracle`, from the jump in ciphertext length as
+/// the input grows.
+pub fn block_size(oracle: &EncryptionOracle) -> usize {
+    let base = oracle.encrypt(b"").len();
+
+    (1..)
+        .map(|length| oracle.encrypt(&vec![b'A'; length]).len())
+        .find(|length| *length > base)
+        .expect("ciphertext never grows")
+        - base
+}
+
+/// Decrypt the secret `oracle` appends to its input, one byte at a time.
+///
+/// Input one byte short of a block boundary leaves the next unknown byte as
+/// the last byte of its block, whose ciphertext is then matched against all
+/// 256 candidates for that byte. The search stops at the first byte of
+/// padding, which changes as the input grows.
+pub fn recover_secret(oracle: &EncryptionOracle) -> Buffer {
+    let size = block_size(oracle);
+    let mut secret = Vec::new();
+
+    loop {
+        let filler = vec![b'A'; size - 1 - secret.len() % size];
+        let block = secret.len() / size * size..(secret.len() / size + 1) * size;
+        let target = oracle.encrypt(&filler);
+
+        let mut probe = filler;
+        probe.extend(&secret);
+        probe.push(0);
+        let last = probe.len() - 1;
+
+        let byte = (0..=255).find(|byte| {
+            probe[last] = *byte;
+            oracle.encrypt(&probe).as_ref()[block.clone()] == target.as_ref()[block.clone()]
+        });
+
+        match byte {
+            Some(byte) => secret.push(byte),
+            None => break,
+        }
+    }
+
+    let mut secret = Buffer::new(&secret);
+    secret.unpad();
+
+    secret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{ChaCha20Rng, TEST_SEED};
+
+    #[test]
+    fn byte_at_a_time_recovers_secret() {
+        let oracle = EncryptionOracle::new(&mut ChaCha20Rng::seed_from_u64(TEST_SEED));
+
+        assert_eq!(block_size(&oracle), 16);
+
+        let secret = recover_secret(&oracle);
+
+        assert_eq!(secret, *crate::SECRET);
+        assert!(secret.to_string().starts_with("Rollin' in my 5.0\n"));
+    }
+}
@@ -0,0 +1,46 @@
+use std::cell::Cell;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Wall-clock time in whole seconds, injectable so that waiting can be
+/// simulated.
+pub trait Clock {
+    /// Seconds since the Unix epoch.
+    fn now(&self) -> u64;
+
+    fn sleep(&self, seconds: u64);
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system clock is before the Unix epoch")
+            .as_secs()
+    }
+
+    fn sleep(&self, seconds: u64) {
+        thread::sleep(Duration::from_secs(seconds));
+    }
+}
+
+/// A clock that only moves when slept on.
+pub struct SimulatedClock(Cell<u64>);
+
+impl SimulatedClock {
+    pub fn new(now: u64) -> Self {
+        Self(Cell::new(now))
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now(&self) -> u64 {
+        self.0.get()
+    }
+
+    fn sleep(&self, seconds: u64) {
+        self.0.set(self.0.get() + seconds);
+    }
+}
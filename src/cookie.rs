@@ -0,0 +1,275 @@
+use crate::crypto::CtrLayout;
+use crate::key_value::KeyValue;
+use crate::random::RandomSource;
+use crate::Buffer;
+use std::error;
+use std::fmt;
+
+const BLOCK_SIZE: usize = 16;
+
+pub const PREFIX: &str = "comment1=cooking%20MCs;userdata=";
+pub const SUFFIX: &str = ";comment2=%20like%20a%20pound%20of%20bacon";
+
+const ADMIN: &[u8] = b";admin=true;";
+
+pub fn quote(userdata: &str) -> String {
+    userdata.replace(';', "%3B").replace('=', "%3D")
+}
+
+pub fn encode_userdata(userdata: &str) -> String {
+    format!("{}{}{}", PREFIX, quote(userdata), SUFFIX)
+}
+
+pub fn is_admin(cookie: &[u8]) -> bool {
+    cookie.windows(ADMIN.len()).any(|window| window == ADMIN)
+}
+
+pub struct CbcCookieService {
+    key: Vec<u8>,
+    iv: Vec<u8>,
+}
+
+impl CbcCookieService {
+    pub fn new(random: &mut dyn RandomSource) -> Self {
+        Self {
+            key: random.bytes(BLOCK_SIZE),
+            iv: random.bytes(BLOCK_SIZE),
+        }
+    }
+
+    pub fn encrypt(&self, userdata: &str) -> Buffer {
+        Buffer::new(encode_userdata(userdata).as_bytes()).aes_128_cbc_encrypt(&self.key, &self.iv)
+    }
+
+    pub fn decrypt(&self, cookie: &Buffer) -> Buffer {
+        let mut plaintext = cookie.aes_128_cbc_decrypt(&self.key, &self.iv);
+        plaintext.unpad();
+
+        plaintext
+    }
+
+    pub fn parse(&self, cookie: &Buffer) -> KeyValue {
+        KeyValue::decode_with(&self.decrypt(cookie).to_string(), ';')
+    }
+
+    pub fn is_admin(&self, cookie: &Buffer) -> bool {
+        is_admin(self.decrypt(cookie).as_ref())
+    }
+}
+
+pub struct CtrCookieService {
+    key: Vec<u8>,
+    layout: CtrLayout,
+}
+
+impl CtrCookieService {
+    pub fn new(random: &mut dyn RandomSource) -> Self {
+        Self {
+            key: random.bytes(BLOCK_SIZE),
+            layout: CtrLayout::LittleEndian64 {
+                nonce: random.next_u64(),
+            },
+        }
+    }
+
+    pub fn encrypt(&self, userdata: &str) -> Buffer {
+        Buffer::new(encode_userdata(userdata).as_bytes()).aes_128_ctr(&self.key, self.layout)
+    }
+
+    pub fn decrypt(&self, cookie: &Buffer) -> Buffer {
+        cookie.aes_128_ctr(&self.key, self.layout)
+    }
+
+    pub fn parse(&self, cookie: &Buffer) -> KeyValue {
+        KeyValue::decode_with(&self.decrypt(cookie).to_string(), ';')
+    }
+
+    pub fn is_admin(&self, cookie: &Buffer) -> bool {
+        is_admin(self.decrypt(cookie).as_ref())
+    }
+}
+
+/// A decrypted cookie that is not plain ASCII, reported back with the
+/// offending plaintext as a careless service would.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidPlaintext {
+    pub plaintext: Buffer,
+}
+
+impl fmt::Display for InvalidPlaintext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid plaintext: {}", self.plaintext.as_hex())
+    }
+}
+
+impl error::Error for InvalidPlaintext {}
+
+/// The CBC cookie service, except that it uses its key as the IV.
+pub struct KeyIvCookieService {
+    key: Vec<u8>,
+}
+
+impl KeyIvCookieService {
+    pub fn new(random: &mut dyn RandomSource) -> Self {
+        Self {
+            key: random.bytes(BLOCK_SIZE),
+        }
+    }
+
+    pub fn encrypt(&self, userdata: &str) -> Buffer {
+        Buffer::new(encode_userdata(userdata).as_bytes()).aes_128_cbc_encrypt(&self.key, &self.key)
+    }
+
+    pub fn decrypt(&self, cookie: &Buffer) -> Result<Buffer, InvalidPlaintext> {
+        let mut plaintext = cookie.aes_128_cbc_decrypt(&self.key, &self.key);
+        plaintext.unpad();
+
+        if plaintext.as_ref().is_ascii() {
+            Ok(plaintext)
+        } else {
+            Err(InvalidPlaintext { plaintext })
+        }
+    }
+
+    pub fn is_admin(&self, cookie: &Buffer) -> Result<bool, InvalidPlaintext> {
+        Ok(is_admin(self.decrypt(cookie)?.as_ref()))
+    }
+
+    /// Whether `key` is the secret key, so that tests can check a recovery.
+    #[cfg(test)]
+    pub fn has_key(&self, key: &[u8]) -> bool {
+        self.key == key
+    }
+}
+
+/// Recover the key of a service that uses it as the IV.
+///
+/// Decrypting `C1 || 0 || C1` gives `P1 = D(C1) ^ key` as the first block
+/// and `D(C1) ^ 0` as the third, so XORing the two blocks from the error
+/// message yields the key.
+pub fn recover_key_iv(service: &KeyIvCookieService) -> Option<Vec<u8>> {
+    let cookie = service.encrypt("");
+    let first = &cookie.as_ref()[..BLOCK_SIZE];
+
+    let mut forged = first.to_vec();
+    forged.extend([0; BLOCK_SIZE]);
+    forged.extend(first);
+    forged.extend(&cookie.as_ref()[3 * BLOCK_SIZE..]);
+
+    let plaintext = service.decrypt(&Buffer::new(&forged)).err()?.plaintext;
+    let blocks = plaintext.as_ref();
+
+    Some(
+        Buffer::new(&blocks[..BLOCK_SIZE])
+            .xor(&blocks[2 * BLOCK_SIZE..3 * BLOCK_SIZE])
+            .as_ref()
+            .to_vec(),
+    )
+}
+
+/// `;admin=true;` with `;` and `=` replaced so that it survives quoting,
+/// together with the XOR delta turning it back.
+fn admin_placeholder() -> (String, Vec<u8>) {
+    let placeholder = ADMIN
+        .iter()
+        .map(|byte| match byte {
+            b';' | b'=' => b'?',
+            byte => *byte,
+        })
+        .collect::<Vec<u8>>();
+
+    let delta = Buffer::new(&placeholder).xor(ADMIN);
+
+    (
+        String::from_utf8_lossy(&placeholder).into_owned(),
+        delta.as_ref().to_vec(),
+    )
+}
+
+/// Inject `;admin=true;` by flipping bits in the block preceding it.
+///
+/// Flipping a ciphertext bit in CBC flips the same bit in the next plaintext
+/// block, at the cost of scrambling the block that was flipped. The userdata
+/// therefore finishes the prefix block and adds one sacrificial block before
+/// the placeholder.
+pub fn cbc_bit_flip(service: &CbcCookieService) -> Buffer {
+    let (placeholder, delta) = admin_placeholder();
+    let filler = (BLOCK_SIZE - PREFIX.len() % BLOCK_SIZE) % BLOCK_SIZE;
+    let userdata = format!("{}{}", "A".repeat(filler + BLOCK_SIZE), placeholder);
+
+    flip(&service.encrypt(&userdata), PREFIX.len() + filler, &delta)
+}
+
+/// Inject `;admin=true;` by flipping bits right where it is to appear.
+///
+/// CTR XORs the plaintext with a keystream, so a bit flipped in the
+/// ciphertext flips the same bit of the plaintext and nothing else, and the
+/// placeholder needs neither alignment nor a sacrificial block.
+pub fn ctr_bit_flip(service: &CtrCookieService) -> Buffer {
+    let (placeholder, delta) = admin_placeholder();
+
+    flip(&service.encrypt(&placeholder), PREFIX.len(), &delta)
+}
+
+/// XOR `delta` into `cookie` at `offset`.
+fn flip(cookie: &Buffer, offset: usize, delta: &[u8]) -> Buffer {
+    let mut mask = vec![0; cookie.len()];
+    mask[offset..offset + delta.len()].copy_from_slice(delta);
+
+    cookie.xor(mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{ChaCha20Rng, TEST_SEED};
+
+    #[test]
+    fn userdata_cannot_smuggle_admin() {
+        let service = CbcCookieService::new(&mut ChaCha20Rng::seed_from_u64(TEST_SEED));
+        let cookie = service.encrypt(";admin=true;");
+
+        assert!(!service.is_admin(&cookie));
+        assert_eq!(
+            service.parse(&cookie).get("userdata"),
+            Some("%3Badmin%3Dtrue%3B")
+        );
+    }
+
+    #[test]
+    fn cbc_bit_flip_injects_admin() {
+        let service = CbcCookieService::new(&mut ChaCha20Rng::seed_from_u64(TEST_SEED));
+        let forged = cbc_bit_flip(&service);
+
+        assert!(service.is_admin(&forged));
+        assert_eq!(service.parse(&forged).get("admin"), Some("true"));
+    }
+
+    #[test]
+    fn ctr_bit_flip_injects_admin() {
+        let service = CtrCookieService::new(&mut ChaCha20Rng::seed_from_u64(TEST_SEED));
+
+        assert!(!service.is_admin(&service.encrypt(";admin=true;")));
+
+        let forged = ctr_bit_flip(&service);
+
+        assert!(service.is_admin(&forged));
+        assert_eq!(service.parse(&forged).get("admin"), Some("true"));
+        assert_eq!(
+            service.parse(&forged).get("comment2"),
+            Some("%20like%20a%20pound%20of%20bacon")
+        );
+    }
+
+    #[test]
+    fn key_as_iv_leaks_key() {
+        let service = KeyIvCookieService::new(&mut ChaCha20Rng::seed_from_u64(TEST_SEED));
+        let key = recover_key_iv(&service).unwrap();
+
+        assert!(service.has_key(&key));
+
+        let cookie = Buffer::new(b"comment1=x;admin=true;").aes_128_cbc_encrypt(&key, &key);
+
+        assert_eq!(service.is_admin(&cookie), Ok(true));
+    }
+}
@@ -0,0 +1,466 @@
+use crate::Buffer;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+/// Ciphertexts sharing one keystream, with the keystream bytes pinned so far;
+/// the keystream is as long as the longest ciphertext.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workbench {
+    ciphertexts: Vec<Buffer>,
+    keystream: Vec<Option<u8>>,
+}
+
+impl Workbench {
+    pub fn new(ciphertexts: Vec<Buffer>) -> Self {
+        let longest = ciphertexts
+            .iter()
+            .map(Buffer::len)
+            .max()
+            .unwrap_or_default();
+
+        Self {
+            ciphertexts,
+            keystream: vec![None; longest],
+        }
+    }
+
+    /// Start from a guessed keystream, typically the statistical solution.
+    pub fn seed(&mut self, keystream: &Buffer) {
+        for (pinned, byte) in self.keystream.iter_mut().zip(keystream.as_ref()) {
+            *pinned = Some(*byte);
+        }
+    }
+
+    pub fn ciphertexts(&self) -> &[Buffer] {
+        &self.ciphertexts
+    }
+
+    pub fn keystream(&self) -> &[Option<u8>] {
+        &self.keystream
+    }
+
+    /// Slide `crib` across ciphertexts `one` and `two` XORed together.
+    ///
+    /// Wherever `one` holds `crib`, the result at that offset is the matching
+    /// plaintext of `two`; offsets that only give printable text are kept.
+    pub fn drag(&self, one: usize, two: usize, crib: &[u8]) -> Vec<(usize, Buffer)> {
+        let one = self.ciphertexts[one].as_ref();
+        let two = self.ciphertexts[two].as_ref();
+        let length = std::cmp::min(one.len(), two.len());
+
+        if crib.is_empty() || crib.len() > length {
+            return Vec::new();
+        }
+
+        (0..=length - crib.len())
+            .map(|offset| {
+                let range = offset..offset + crib.len();
+                let fragment = Buffer::new(&one[range.clone()]).xor(&two[range]).xor(crib);
+
+                (offset, fragment)
+            })
+            .filter(|(_, fragment)| fragment.as_ref().iter().all(printable))
+            .collect()
+    }
+
+    /// Assume ciphertext `index` holds `crib` at `offset` and show what every
+    /// ciphertext would hold there; `None` for those too short to reach it.
+    pub fn fragments(&self, index: usize, offset: usize, crib: &[u8]) -> Vec<Option<Buffer>> {
+        let keystream = self.keystream_for(index, offset, crib);
+
+        self.ciphertexts
+            .iter()
+            .map(|ciphertext| {
+                ciphertext
+                    .as_ref()
+                    .get(offset..offset.checked_add(crib.len())?)
+                    .map(|slice| Buffer::new(slice).xor(&keystream))
+            })
+            .collect()
+    }
+
+    /// Pin the keystream byte at `offset`, which must lie within the longest
+    /// ciphertext.
+    pub fn pin(&mut self, offset: usize, byte: u8) {
+        assert!(
+            offset < self.keystream.len(),
+            "offset past the longest ciphertext"
+        );
+
+        self.keystream[offset] = Some(byte);
+    }
+
+    pub fn unpin(&mut self, offset: usize) {
+        if let Some(byte) = self.keystream.get_mut(offset) {
+            *byte = None;
+        }
+    }
+
+    /// Pin the keystream bytes that make ciphertext `index` read `plaintext`
+    /// at `offset`.
+    pub fn pin_plaintext(&mut self, index: usize, offset: usize, plaintext: &[u8]) {
+        for (i, byte) in self
+            .keystream_for(index, offset, plaintext)
+            .into_iter()
+            .enumerate()
+        {
+            self.pin(offset + i, byte);
+        }
+    }
+
+    /// Decrypt ciphertext `index` as far as the keystream is pinned, showing
+    /// unknown bytes as `_` and unprintable ones as `?`.
+    pub fn decrypt(&self, index: usize) -> String {
+        self.ciphertexts[index]
+            .as_ref()
+            .iter()
+            .zip(self.keystream.iter())
+            .map(|(byte, key)| match key {
+                Some(key) if printable(&(byte ^ key)) => (byte ^ key) as char,
+                Some(_) => '?',
+                None => '_',
+            })
+            .collect()
+    }
+
+    fn keystream_for(&self, index: usize, offset: usize, plaintext: &[u8]) -> Vec<u8> {
+        let ciphertext = self.ciphertexts[index].as_ref();
+        let end = std::cmp::min(ciphertext.len(), offset.saturating_add(plaintext.len()));
+
+        if offset >= end {
+            return Vec::new();
+        }
+
+        Buffer::new(&ciphertext[offset..end])
+            .xor(plaintext)
+            .as_ref()
+            .to_vec()
+    }
+
+    /// Write the session as `ciphertext <hex>` and `key <offset> <hex>` lines.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let ciphertexts = self
+            .ciphertexts
+            .iter()
+            .map(|ciphertext| format!("ciphertext {}\n", ciphertext.as_hex()));
+
+        let keystream = self
+            .keystream
+            .iter()
+            .enumerate()
+            .filter_map(|(offset, byte)| byte.map(|byte| format!("key {} {:02x}\n", offset, byte)));
+
+        fs::write(path, ciphertexts.chain(keystream).collect::<String>())
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let invalid = |line: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("malformed session line {:?}", line),
+            )
+        };
+
+        let mut ciphertexts = Vec::new();
+        let mut pins = Vec::new();
+
+        let session = fs::read_to_string(path)?;
+
+        for line in session.lines() {
+            let words = line.split_whitespace().collect::<Vec<&str>>();
+
+            match words.as_slice() {
+                [] => (),
+
+                ["ciphertext", hex] => {
+                    ciphertexts.push(Buffer::new(&hex::decode(hex).map_err(|_| invalid(line))?))
+                }
+
+                ["key", offset, byte] => pins.push((
+                    offset.parse::<usize>().map_err(|_| invalid(line))?,
+                    u8::from_str_radix(byte, 16).map_err(|_| invalid(line))?,
+                    line,
+                )),
+
+                _ => return Err(invalid(line)),
+            }
+        }
+
+        let mut workbench = Self::new(ciphertexts);
+
+        for (offset, byte, line) in pins {
+            if offset >= workbench.keystream.len() {
+                return Err(invalid(line));
+            }
+
+            workbench.pin(offset, byte);
+        }
+
+        Ok(workbench)
+    }
+}
+
+fn printable(byte: &u8) -> bool {
+    byte.is_ascii_graphic() || *byte == b' '
+}
+
+const HELP: &str = "\
+show                          decrypt every ciphertext with the pinned keystream
+drag <one> <two> <crib>       slide crib across ciphertexts one and two XORed
+try <index> <offset> <crib>   show all ciphertexts if index held crib at offset
+pin <index> <offset> <text>   pin the keystream so index reads text at offset
+key <offset> <hex>            pin a single keystream byte
+unpin <offset> [count]        forget pinned keystream bytes
+save                          write the session file
+quit                          save and leave";
+
+/// Run the crib-dragging prompt on `workbench`, saving to `session` after
+/// every change so the work can be resumed later.
+pub fn repl<R: BufRead, W: Write>(
+    workbench: &mut Workbench,
+    session: &Path,
+    input: R,
+    mut output: W,
+) -> io::Result<()> {
+    write!(output, "> ")?;
+    output.flush()?;
+
+    for line in input.lines() {
+        let line = line?;
+        let (command, rest) = line
+            .trim_start()
+            .split_once(' ')
+            .unwrap_or((line.trim(), ""));
+
+        match run_command(workbench, command, rest) {
+            Ok(Outcome::Print(text)) => writeln!(output, "{}", text)?,
+
+            Ok(Outcome::Changed) => {
+                workbench.save(session)?;
+                writeln!(output, "{}", show(workbench))?;
+            }
+
+            Ok(Outcome::Save) => workbench.save(session)?,
+
+            Ok(Outcome::Quit) => break,
+
+            Err(message) => writeln!(output, "{}", message)?,
+        }
+
+        write!(output, "> ")?;
+        output.flush()?;
+    }
+
+    workbench.save(session)
+}
+
+enum Outcome {
+    Print(String),
+    Changed,
+    Save,
+    Quit,
+}
+
+fn run_command(workbench: &mut Workbench, command: &str, rest: &str) -> Result<Outcome, String> {
+    let count = workbench.ciphertexts().len();
+
+    let index = |word: &str| match word.parse::<usize>() {
+        Ok(index) if index < count => Ok(index),
+        _ => Err(format!(
+            "{:?} is not a ciphertext index below {}",
+            word, count
+        )),
+    };
+
+    let number = |word: &str| {
+        word.parse::<usize>()
+            .map_err(|_| format!("{:?} is not a number", word))
+    };
+
+    // Nothing lies beyond the longest ciphertext.
+    let longest = workbench.keystream().len();
+
+    let offset = |word: &str| match number(word)? {
+        offset if offset < longest => Ok(offset),
+        _ => Err(format!("{:?} is not an offset below {}", word, longest)),
+    };
+
+    let mut words = rest.splitn(3, ' ');
+    let mut next = || {
+        words
+            .next()
+            .filter(|word| !word.is_empty())
+            .ok_or("missing argument")
+    };
+
+    match command {
+        "" => Ok(Outcome::Print(String::new())),
+
+        "help" => Ok(Outcome::Print(String::from(HELP))),
+
+        "show" => Ok(Outcome::Print(show(workbench))),
+
+        "drag" => {
+            let (one, two) = (index(next()?)?, index(next()?)?);
+            let crib = next()?;
+
+            Ok(Outcome::Print(
+                workbench
+                    .drag(one, two, crib.as_bytes())
+                    .into_iter()
+                    .map(|(offset, fragment)| format!("{:4}  {}", offset, fragment))
+                    .collect::<Vec<String>>()
+                    .join("\n"),
+            ))
+        }
+
+        "try" => {
+            let (index, offset) = (index(next()?)?, offset(next()?)?);
+            let crib = next()?;
+
+            Ok(Outcome::Print(
+                workbench
+                    .fragments(index, offset, crib.as_bytes())
+                    .into_iter()
+                    .enumerate()
+                    .filter_map(|(i, fragment)| fragment.map(|text| format!("{:4}  {}", i, text)))
+                    .collect::<Vec<String>>()
+                    .join("\n"),
+            ))
+        }
+
+        "pin" => {
+            let (index, offset) = (index(next()?)?, offset(next()?)?);
+            workbench.pin_plaintext(index, offset, next()?.as_bytes());
+
+            Ok(Outcome::Changed)
+        }
+
+        "key" => {
+            let offset = offset(next()?)?;
+            let byte = next()?;
+            let byte = u8::from_str_radix(byte, 16)
+                .map_err(|_| format!("{:?} is not a hex byte", byte))?;
+
+            workbench.pin(offset, byte);
+
+            Ok(Outcome::Changed)
+        }
+
+        "unpin" => {
+            let start = offset(next()?)?;
+            let count = next().ok().map_or(Ok(1), number)?;
+            let end = start
+                .checked_add(count)
+                .filter(|end| *end <= longest)
+                .ok_or(format!(
+                    "{} bytes from {} run past offset {}",
+                    count, start, longest
+                ))?;
+
+            for offset in start..end {
+                workbench.unpin(offset);
+            }
+
+            Ok(Outcome::Changed)
+        }
+
+        "save" => Ok(Outcome::Save),
+
+        "quit" | "exit" => Ok(Outcome::Quit),
+
+        _ => Err(format!("unknown command {:?}; try help", command)),
+    }
+}
+
+fn show(workbench: &Workbench) -> String {
+    (0..workbench.ciphertexts().len())
+        .map(|index| format!("{:4}  {}", index, workbench.decrypt(index)))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{ChaCha20Rng, RandomSource, TEST_SEED};
+
+    const PLAINTEXTS: [&[u8]; 3] = [
+        b"the quick brown fox",
+        b"jumps over the lazy dog",
+        b"and then the dog",
+    ];
+
+    fn workbench() -> Workbench {
+        let keystream = ChaCha20Rng::seed_from_u64(TEST_SEED).bytes(32);
+
+        Workbench::new(
+            PLAINTEXTS
+                .iter()
+                .map(|plaintext| Buffer::new(plaintext).xor(&keystream))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn dragging_a_crib_reveals_the_other_plaintext() {
+        let workbench = workbench();
+        let fragments = workbench.drag(1, 0, b" the ");
+
+        assert!(fragments
+            .iter()
+            .any(|(offset, fragment)| *offset == 10 && fragment.as_ref() == b"brown"));
+    }
+
+    #[test]
+    fn pinned_plaintext_decrypts_every_ciphertext() {
+        let mut workbench = workbench();
+        workbench.pin_plaintext(1, 0, PLAINTEXTS[1]);
+
+        assert_eq!(workbench.decrypt(0), "the quick brown fox");
+        assert_eq!(workbench.decrypt(2), "and then the dog");
+
+        workbench.unpin(4);
+        assert_eq!(workbench.decrypt(2), "and _hen the dog");
+    }
+
+    #[test]
+    fn session_resumes_from_file() {
+        let path = std::env::temp_dir().join(format!("crib-{}.session", std::process::id()));
+        let mut workbench = workbench();
+
+        let input = io::Cursor::new("pin 0 0 the quick\nkey 20 ff\nunpin 3\nquit\n");
+        repl(&mut workbench, &path, input, io::sink()).unwrap();
+
+        let resumed = Workbench::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(resumed, workbench);
+        assert_eq!(resumed.decrypt(0), "the_quick__________");
+        assert_eq!(resumed.keystream()[20], Some(0xff));
+    }
+
+    #[test]
+    fn out_of_range_commands_are_rejected() {
+        let path = std::env::temp_dir().join(format!("crib-range-{}.session", std::process::id()));
+        let mut workbench = workbench();
+        let mut output = Vec::new();
+
+        let input = io::Cursor::new(
+            "key 18446744073709551615 ff\n\
+             try 0 18446744073709551615 the\n\
+             unpin 0 18446744073709551615\n\
+             unpin 20 4\n\
+             quit\n",
+        );
+        repl(&mut workbench, &path, input, &mut output).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+
+        assert_eq!(output.matches("is not an offset below 23").count(), 2);
+        assert_eq!(output.matches("run past offset 23").count(), 2);
+        assert_eq!(workbench, self::workbench());
+    }
+}
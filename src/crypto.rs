@@ -1,4 +1,4 @@
-pub fn fixed_xor<T: AsRef<[u8]>, U: AsRef<[u8]>>(a: T, b: T) -> Vec<u8> {
+pub fn fixed_xor<T: AsRef<[u8]>>(a: T, b: T) -> Vec<u8> {
     assert!(
         a.as_ref().len() == b.as_ref().len(),
         "input streams do not have equal length"
@@ -15,7 +15,7 @@ pub fn aes_128_ecb_encrypt(key: &[u8], input: &[u8]) -> Vec<u8> {
     let cipher = openssl::symm::Cipher::aes_128_ecb();
     let mut output = openssl::symm::encrypt(cipher, key, None, input).expect("encryption failed");
 
-    if input.len() % 16 == 0 {
+    if input.len().is_multiple_of(16) {
         output.truncate(output.len() - 16);
     }
 
@@ -23,7 +23,7 @@ pub fn aes_128_ecb_encrypt(key: &[u8], input: &[u8]) -> Vec<u8> {
 }
 
 pub fn aes_128_ecb_decrypt(key: &[u8], input: &[u8]) -> Vec<u8> {
-    assert!(input.len() % 16 == 0, "malformed input");
+    assert!(input.len().is_multiple_of(16), "malformed input");
 
     let mut crypter = openssl::symm::Crypter::new(
         openssl::symm::Cipher::aes_128_ecb(),
@@ -48,3 +48,267 @@ pub fn aes_128_ecb_decrypt(key: &[u8], input: &[u8]) -> Vec<u8> {
 
     output
 }
+
+/// Counter block layouts for CTR mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CtrLayout {
+    /// 64-bit little-endian nonce followed by a 64-bit little-endian block
+    /// counter starting at zero, as used by cryptopals.
+    LittleEndian64 { nonce: u64 },
+
+    /// 96-bit nonce followed by a 32-bit big-endian block counter, as in
+    /// NIST SP 800-38A and most protocols. The keystream ends where the
+    /// counter would pass `u32::MAX`.
+    BigEndian96 {
+        nonce: [u8; 12],
+        initial_counter: u32,
+    },
+}
+
+impl CtrLayout {
+    /// The counter block for keystream block `block`, or `None` once the
+    /// counter would wrap and repeat an earlier block.
+    fn counter_block(&self, block: u64) -> Option<[u8; 16]> {
+        let mut counter = [0; 16];
+
+        match self {
+            Self::LittleEndian64 { nonce } => {
+                counter[..8].copy_from_slice(&nonce.to_le_bytes());
+                counter[8..].copy_from_slice(&block.to_le_bytes());
+            }
+
+            Self::BigEndian96 {
+                nonce,
+                initial_counter,
+            } => {
+                let block = u32::try_from(block)
+                    .ok()
+                    .and_then(|block| initial_counter.checked_add(block))?;
+
+                counter[..12].copy_from_slice(nonce);
+                counter[12..].copy_from_slice(&block.to_be_bytes());
+            }
+        }
+
+        Some(counter)
+    }
+}
+
+/// A seekable keystream; encryption and decryption both XOR it into the data.
+pub trait Keystream: Iterator<Item = u8> {
+    /// Position the keystream at byte `offset` from its start.
+    fn seek(&mut self, offset: u64);
+
+    fn apply(&mut self, data: &[u8]) -> Vec<u8> {
+        data.iter()
+            .map(|byte| byte ^ self.next().expect("keystream exhausted"))
+            .collect()
+    }
+}
+
+pub struct AesCtr {
+    crypter: openssl::symm::Crypter,
+    layout: CtrLayout,
+    block: u64,
+    position: usize,
+    /// The current keystream block, or `None` once the counter is exhausted.
+    keystream: Option<[u8; 16]>,
+}
+
+impl AesCtr {
+    pub fn new(key: &[u8], layout: CtrLayout) -> Self {
+        let mut crypter = openssl::symm::Crypter::new(
+            openssl::symm::Cipher::aes_128_ecb(),
+            openssl::symm::Mode::Encrypt,
+            key,
+            None,
+        )
+        .expect("malformed key");
+
+        crypter.pad(false);
+
+        let mut ctr = Self {
+            crypter,
+            layout,
+            block: 0,
+            position: 0,
+            keystream: None,
+        };
+
+        ctr.generate();
+        ctr
+    }
+
+    fn generate(&mut self) {
+        self.keystream = self.layout.counter_block(self.block).map(|counter| {
+            let mut buffer = [0; 32];
+
+            self.crypter
+                .update(&counter, &mut buffer)
+                .expect("encryption failed");
+
+            buffer[0..16].try_into().unwrap()
+        });
+    }
+}
+
+impl Iterator for AesCtr {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position == 16 {
+            self.block = self.block.checked_add(1)?;
+            self.position = 0;
+            self.generate();
+        }
+
+        let byte = self.keystream?[self.position];
+        self.position += 1;
+
+        Some(byte)
+    }
+}
+
+impl Keystream for AesCtr {
+    fn seek(&mut self, offset: u64) {
+        let block = offset / 16;
+
+        if block != self.block {
+            self.block = block;
+            self.generate();
+        }
+
+        self.position = (offset % 16) as usize;
+    }
+}
+
+pub fn aes_128_ctr(key: &[u8], layout: CtrLayout, input: &[u8]) -> Vec<u8> {
+    AesCtr::new(key, layout).apply(input)
+}
+
+/// Replace the plaintext under `ciphertext` from `offset` on with `newtext`,
+/// re-encrypting only the bytes touched. The ciphertext grows if `newtext`
+/// runs past its end, and `None` is returned if `offset` already does.
+pub fn aes_128_ctr_edit(
+    ciphertext: &[u8],
+    key: &[u8],
+    layout: CtrLayout,
+    offset: usize,
+    newtext: &[u8],
+) -> Option<Vec<u8>> {
+    if offset > ciphertext.len() {
+        return None;
+    }
+
+    let mut ctr = AesCtr::new(key, layout);
+    ctr.seek(offset as u64);
+
+    let mut edited = ciphertext.to_vec();
+    edited.resize(ciphertext.len().max(offset + newtext.len()), 0);
+    edited[offset..offset + newtext.len()].copy_from_slice(&ctr.apply(newtext));
+
+    Some(edited)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &[u8] = b"YELLOW SUBMARINE";
+
+    #[test]
+    fn ctr_decrypts_little_endian_layout() {
+        let ciphertext = crate::base64::decode(
+            "L77na/nrFsKvynd6HzOoG7GHTLXsTVu9qvY/2syLXzhPweyyMTJULu/6/kXX0KSvoOLSFQ==",
+        );
+
+        assert_eq!(
+            aes_128_ctr(KEY, CtrLayout::LittleEndian64 { nonce: 0 }, &ciphertext),
+            b"Yo, VIP Let's kick it Ice, Ice, baby Ice, Ice, baby "
+        );
+    }
+
+    #[test]
+    fn ctr_matches_nist_sp800_38a() {
+        let key = hex::decode("2b7e151628aed2a6abf7158809cf4f3c").unwrap();
+        let layout = CtrLayout::BigEndian96 {
+            nonce: hex::decode("f0f1f2f3f4f5f6f7f8f9fafb")
+                .unwrap()
+                .try_into()
+                .unwrap(),
+            initial_counter: 0xfcfdfeff,
+        };
+
+        let plaintext = hex::decode(
+            "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51\
+             30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710",
+        )
+        .unwrap();
+
+        assert_eq!(
+            hex::encode(aes_128_ctr(&key, layout, &plaintext)),
+            "874d6191b620e3261bef6864990db6ce9806f66b7970fdff8617187bb9fffdff\
+             5ae4df3edbd5d35e5b4f09020db03eab1e031dda2fbe03d1792170a0f3009cee"
+        );
+    }
+
+    #[test]
+    fn ctr_counter_never_wraps() {
+        let layout = CtrLayout::BigEndian96 {
+            nonce: [0; 12],
+            initial_counter: u32::MAX - 1,
+        };
+
+        assert_eq!(AesCtr::new(KEY, layout).count(), 32);
+
+        let mut ctr = AesCtr::new(
+            KEY,
+            CtrLayout::BigEndian96 {
+                nonce: [0; 12],
+                initial_counter: 0,
+            },
+        );
+        ctr.seek(16 << 32);
+
+        assert_eq!(ctr.next(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "keystream exhausted")]
+    fn ctr_refuses_to_reuse_keystream() {
+        let layout = CtrLayout::BigEndian96 {
+            nonce: [0; 12],
+            initial_counter: u32::MAX,
+        };
+
+        aes_128_ctr(KEY, layout, &[0; 17]);
+    }
+
+    #[test]
+    fn ctr_seeks_to_any_offset() {
+        let layout = CtrLayout::LittleEndian64 { nonce: 7 };
+        let keystream: Vec<u8> = AesCtr::new(KEY, layout).take(100).collect();
+
+        let mut ctr = AesCtr::new(KEY, layout);
+
+        for offset in [37, 3, 16, 99, 0, 64] {
+            ctr.seek(offset as u64);
+            assert_eq!(ctr.next(), Some(keystream[offset]));
+        }
+    }
+
+    #[test]
+    fn ctr_edit_rewrites_a_slice() {
+        let layout = CtrLayout::LittleEndian64 { nonce: 3 };
+        let ciphertext = aes_128_ctr(KEY, layout, b"attack at dawn");
+
+        let edited = aes_128_ctr_edit(&ciphertext, KEY, layout, 10, b"dusk, then retreat").unwrap();
+
+        assert_eq!(edited[..10], ciphertext[..10]);
+        assert_eq!(
+            aes_128_ctr(KEY, layout, &edited),
+            b"attack at dusk, then retreat"
+        );
+        assert_eq!(aes_128_ctr_edit(&ciphertext, KEY, layout, 15, b"!"), None);
+    }
+}
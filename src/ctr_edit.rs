@@ -0,0 +1,65 @@
+use crate::crypto::{self, CtrLayout};
+use crate::random::RandomSource;
+use crate::Buffer;
+
+/// Random-access storage encrypted under AES-CTR with a secret key, which
+/// lets callers overwrite any part of a ciphertext without decrypting it.
+pub struct CtrEditService {
+    key: Vec<u8>,
+    layout: CtrLayout,
+}
+
+impl CtrEditService {
+    pub fn new(random: &mut dyn RandomSource) -> Self {
+        Self {
+            key: random.bytes(16),
+            layout: CtrLayout::LittleEndian64 {
+                nonce: random.next_u64(),
+            },
+        }
+    }
+
+    pub fn encrypt(&self, plaintext: &Buffer) -> Buffer {
+        plaintext.aes_128_ctr(&self.key, self.layout)
+    }
+
+    /// Overwrite the plaintext from `offset` on, or `None` if `offset` lies
+    /// past the end of `ciphertext`.
+    pub fn edit(&self, ciphertext: &Buffer, offset: usize, newtext: &[u8]) -> Option<Buffer> {
+        crypto::aes_128_ctr_edit(ciphertext.as_ref(), &self.key, self.layout, offset, newtext)
+            .map(|edited| Buffer::new(&edited))
+    }
+}
+
+/// Recover the plaintext under `ciphertext` through nothing but `edit`.
+///
+/// Writing zeros over the whole ciphertext hands back the keystream itself,
+/// which XORed into the original ciphertext is the plaintext.
+pub fn recover_plaintext(service: &CtrEditService, ciphertext: &Buffer) -> Buffer {
+    let keystream = service
+        .edit(ciphertext, 0, &vec![0; ciphertext.len()])
+        .expect("offset 0 is within any ciphertext");
+
+    ciphertext.xor(keystream)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{ChaCha20Rng, TEST_SEED};
+    use std::fs;
+
+    #[test]
+    fn edit_recovers_plaintext() {
+        // The challenge's `25.txt` is the same file as `7.txt`.
+        let ecb = Buffer::from_base64(&fs::read_to_string("7.txt").unwrap().replace('\n', ""));
+        let mut plaintext = ecb.aes_128_ecb_decrypt(b"YELLOW SUBMARINE");
+        plaintext.unpad();
+
+        let service = CtrEditService::new(&mut ChaCha20Rng::seed_from_u64(TEST_SEED));
+        let ciphertext = service.encrypt(&plaintext);
+
+        assert_eq!(recover_plaintext(&service, &ciphertext), plaintext);
+        assert_eq!(service.edit(&ciphertext, ciphertext.len() + 1, b"x"), None);
+    }
+}
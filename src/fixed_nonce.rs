@@ -0,0 +1,191 @@
+use crate::Buffer;
+
+/// How to treat the bytes of ciphertexts that are longer than the shortest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tails {
+    /// Drop everything past the length of the shortest ciphertext.
+    Truncate,
+
+    /// Solve every further keystream byte from the ciphertexts long enough to
+    /// reach it; the fewer there are, the less reliable the result.
+    ColumnByColumn,
+}
+
+/// Recover the keystream shared by `ciphertexts` and the plaintexts under it.
+///
+/// Reusing a CTR nonce turns the keystream into a repeating key as wide as
+/// the ciphertexts, so each column is a single-byte XOR on its own. Empty
+/// ciphertexts say nothing about the keystream and decrypt to empty
+/// plaintexts.
+pub fn break_fixed_nonce_ctr(ciphertexts: &[Buffer], tails: Tails) -> (Buffer, Vec<Buffer>) {
+    let shortest = ciphertexts
+        .iter()
+        .map(Buffer::len)
+        .filter(|length| *length > 0)
+        .min()
+        .unwrap_or_default();
+    let longest = ciphertexts
+        .iter()
+        .map(Buffer::len)
+        .max()
+        .unwrap_or_default();
+
+    if shortest == 0 {
+        return (Buffer::new(&[]), vec![Buffer::new(&[]); ciphertexts.len()]);
+    }
+
+    let truncated = Buffer::new(
+        &ciphertexts
+            .iter()
+            .filter(|ciphertext| !ciphertext.is_empty())
+            .flat_map(|ciphertext| ciphertext.as_ref()[..shortest].iter().copied())
+            .collect::<Vec<u8>>(),
+    );
+
+    let mut keystream = truncated
+        .transpose(shortest)
+        .iter()
+        .map(|column| crate::best_one_byte_xor(column).0)
+        .collect::<Vec<u8>>();
+
+    let length = match tails {
+        Tails::Truncate => shortest,
+
+        Tails::ColumnByColumn => {
+            for position in shortest..longest {
+                let column = Buffer::new(
+                    &ciphertexts
+                        .iter()
+                        .filter_map(|ciphertext| ciphertext.as_ref().get(position).copied())
+                        .collect::<Vec<u8>>(),
+                );
+
+                keystream.push(crate::best_one_byte_xor(&column).0);
+            }
+
+            longest
+        }
+    };
+
+    let plaintexts = ciphertexts
+        .iter()
+        .map(|ciphertext| {
+            let end = std::cmp::min(ciphertext.len(), length);
+            Buffer::new(&ciphertext.as_ref()[..end]).xor(&keystream)
+        })
+        .collect();
+
+    (Buffer::new(&keystream), plaintexts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::CtrLayout;
+    use crate::random::{ChaCha20Rng, RandomSource, TEST_SEED};
+    use std::fs;
+
+    fn lyrics() -> Vec<Buffer> {
+        let ciphertext =
+            Buffer::from_base64(&fs::read_to_string("7.txt").unwrap().replace('\n', ""));
+        let mut plaintext = ciphertext.aes_128_ecb_decrypt(b"YELLOW SUBMARINE");
+        plaintext.unpad();
+
+        plaintext
+            .to_string()
+            .lines()
+            .filter(|line| line.len() > 16)
+            .map(|line| Buffer::new(line.as_bytes()))
+            .collect()
+    }
+
+    fn encrypt_all(plaintexts: &[Buffer]) -> Vec<Buffer> {
+        let key = ChaCha20Rng::seed_from_u64(TEST_SEED).bytes(16);
+
+        plaintexts
+            .iter()
+            .map(|plaintext| plaintext.aes_128_ctr(&key, CtrLayout::LittleEndian64 { nonce: 0 }))
+            .collect()
+    }
+
+    fn correct_fraction(recovered: &[Buffer], plaintexts: &[Buffer]) -> f64 {
+        let (correct, total) = recovered
+            .iter()
+            .zip(plaintexts)
+            .flat_map(|(one, two)| one.as_ref().iter().zip(two.as_ref()))
+            .fold((0, 0), |(correct, total), (a, b)| {
+                (correct + usize::from(a.eq_ignore_ascii_case(b)), total + 1)
+            });
+
+        correct as f64 / total as f64
+    }
+
+    #[test]
+    fn fixed_nonce_ctr_truncated() {
+        let plaintexts = lyrics();
+        let (keystream, recovered) =
+            break_fixed_nonce_ctr(&encrypt_all(&plaintexts), Tails::Truncate);
+
+        let shortest = plaintexts.iter().map(Buffer::len).min().unwrap();
+
+        assert_eq!(keystream.len(), shortest);
+        assert!(recovered
+            .iter()
+            .all(|plaintext| plaintext.len() == shortest));
+        assert!(correct_fraction(&recovered, &plaintexts) > 0.8);
+    }
+
+    #[test]
+    fn fixed_nonce_ctr_column_by_column() {
+        let plaintexts = lyrics();
+        let (keystream, recovered) =
+            break_fixed_nonce_ctr(&encrypt_all(&plaintexts), Tails::ColumnByColumn);
+
+        let longest = plaintexts.iter().map(Buffer::len).max().unwrap();
+
+        assert_eq!(keystream.len(), longest);
+        assert!(recovered
+            .iter()
+            .zip(&plaintexts)
+            .all(|(one, two)| one.len() == two.len()));
+        assert!(correct_fraction(&recovered, &plaintexts) > 0.8);
+    }
+
+    #[test]
+    fn keystream_bytes_of_ff_are_recovered() {
+        let plaintexts = lyrics();
+        let mut keystream = ChaCha20Rng::seed_from_u64(TEST_SEED).bytes(16);
+        keystream[4] = 0xff;
+        keystream[9] = 0xff;
+
+        let ciphertexts = plaintexts
+            .iter()
+            .map(|plaintext| Buffer::new(&plaintext.as_ref()[..16]).xor(&keystream))
+            .collect::<Vec<Buffer>>();
+        let (recovered, _) = break_fixed_nonce_ctr(&ciphertexts, Tails::Truncate);
+
+        assert_eq!(recovered.as_ref()[4], 0xff);
+        assert_eq!(recovered.as_ref()[9], 0xff);
+    }
+
+    #[test]
+    fn empty_ciphertexts_decrypt_to_nothing() {
+        let mut plaintexts = lyrics();
+        plaintexts.insert(3, Buffer::new(&[]));
+
+        for tails in [Tails::Truncate, Tails::ColumnByColumn] {
+            let (keystream, recovered) = break_fixed_nonce_ctr(&encrypt_all(&plaintexts), tails);
+
+            assert!(!keystream.is_empty());
+            assert!(recovered[3].is_empty());
+            assert!(correct_fraction(&recovered, &plaintexts) > 0.8);
+        }
+
+        let empty = vec![Buffer::new(&[]); 2];
+
+        assert_eq!(
+            break_fixed_nonce_ctr(&empty, Tails::ColumnByColumn),
+            (Buffer::new(&[]), empty)
+        );
+    }
+}
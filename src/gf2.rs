@@ -0,0 +1,220 @@
+use std::ops;
+
+/// A vector over GF(2), packed 64 bits to a word.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitVector {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitVector {
+    pub fn zeros(len: usize) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(64)],
+            len,
+        }
+    }
+
+    pub fn unit(len: usize, bit: usize) -> Self {
+        let mut vector = Self::zeros(len);
+        vector.set(bit, true);
+        vector
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, bit: usize) -> bool {
+        self.words[bit / 64] >> (bit % 64) & 1 == 1
+    }
+
+    pub fn set(&mut self, bit: usize, value: bool) {
+        if value {
+            self.words[bit / 64] |= 1 << (bit % 64);
+        } else {
+            self.words[bit / 64] &= !(1 << (bit % 64));
+        }
+    }
+
+    /// The lowest set bit at or after `from`.
+    pub fn first_one(&self, from: usize) -> Option<usize> {
+        let start = from / 64;
+
+        if start >= self.words.len() {
+            return None;
+        }
+
+        let first = self.words[start] & (u64::MAX << (from % 64));
+
+        if first != 0 {
+            return Some(start * 64 + first.trailing_zeros() as usize);
+        }
+
+        self.words[start + 1..]
+            .iter()
+            .position(|word| *word != 0)
+            .map(|i| (start + 1 + i) * 64 + self.words[start + 1 + i].trailing_zeros() as usize)
+    }
+
+    /// The inner product with `other`.
+    pub fn dot(&self, other: &Self) -> bool {
+        self.words
+            .iter()
+            .zip(other.words.iter())
+            .map(|(a, b)| (a & b).count_ones())
+            .sum::<u32>()
+            % 2
+            == 1
+    }
+
+    /// XOR `other` into `self`, skipping the words before the one holding
+    /// `from`, which the caller knows to be zero in `other`.
+    fn xor_from(&mut self, other: &Self, from: usize) {
+        for (a, b) in self.words[from / 64..]
+            .iter_mut()
+            .zip(other.words[from / 64..].iter())
+        {
+            *a ^= b;
+        }
+    }
+}
+
+impl ops::BitXorAssign<&BitVector> for BitVector {
+    fn bitxor_assign(&mut self, other: &BitVector) {
+        self.xor_from(other, 0);
+    }
+}
+
+/// A system of linear equations over GF(2), kept in echelon form as the
+/// equations arrive.
+pub struct LinearSystem {
+    unknowns: usize,
+    pivots: Vec<Option<(BitVector, bool)>>,
+    rank: usize,
+    consistent: bool,
+}
+
+impl LinearSystem {
+    pub fn new(unknowns: usize) -> Self {
+        Self {
+            unknowns,
+            pivots: vec![None; unknowns],
+            rank: 0,
+            consistent: true,
+        }
+    }
+
+    pub fn rank(&self) -> usize {
+        self.rank
+    }
+
+    pub fn is_consistent(&self) -> bool {
+        self.consistent
+    }
+
+    /// Add the equation `coefficients · x = value`, returning whether it was
+    /// independent of the ones already added.
+    pub fn add(&mut self, mut coefficients: BitVector, mut value: bool) -> bool {
+        assert_eq!(
+            coefficients.len(),
+            self.unknowns,
+            "equation has wrong width"
+        );
+
+        let mut from = 0;
+
+        while let Some(bit) = coefficients.first_one(from) {
+            match &self.pivots[bit] {
+                Some((pivot, pivot_value)) => {
+                    coefficients.xor_from(pivot, bit);
+                    value ^= pivot_value;
+                    from = bit + 1;
+                }
+
+                None => {
+                    self.pivots[bit] = Some((coefficients, value));
+                    self.rank += 1;
+
+                    return true;
+                }
+            }
+        }
+
+        self.consistent &= !value;
+
+        false
+    }
+
+    /// One solution of the system, with every free unknown set to zero, or
+    /// `None` if the equations contradict each other.
+    pub fn solve(&self) -> Option<BitVector> {
+        if !self.consistent {
+            return None;
+        }
+
+        let mut solution = BitVector::zeros(self.unknowns);
+
+        for (bit, pivot) in self.pivots.iter().enumerate().rev() {
+            if let Some((coefficients, value)) = pivot {
+                // Only unknowns after `bit` are set so far, and `bit` itself
+                // is still zero, so the dot product sums exactly those.
+                solution.set(bit, value ^ coefficients.dot(&solution));
+            }
+        }
+
+        Some(solution)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{ChaCha20Rng, RandomSource, TEST_SEED};
+
+    fn random_vector(random: &mut ChaCha20Rng, len: usize) -> BitVector {
+        let mut vector = BitVector::zeros(len);
+
+        for (bit, byte) in random.bytes(len).into_iter().enumerate() {
+            vector.set(bit, byte & 1 == 1);
+        }
+
+        vector
+    }
+
+    #[test]
+    fn solves_random_full_rank_system() {
+        let mut random = ChaCha20Rng::seed_from_u64(TEST_SEED);
+        let unknowns = 150;
+        let secret = random_vector(&mut random, unknowns);
+        let mut system = LinearSystem::new(unknowns);
+
+        while system.rank() < unknowns {
+            let row = random_vector(&mut random, unknowns);
+            let value = row.dot(&secret);
+
+            system.add(row, value);
+        }
+
+        assert!(system.is_consistent());
+        assert_eq!(system.solve(), Some(secret));
+    }
+
+    #[test]
+    fn detects_contradiction() {
+        let mut system = LinearSystem::new(3);
+
+        assert!(system.add(BitVector::unit(3, 0), true));
+        assert!(system.add(BitVector::unit(3, 1), false));
+
+        let mut sum = BitVector::unit(3, 0);
+        sum ^= &BitVector::unit(3, 1);
+
+        assert!(!system.add(sum, false));
+        assert_eq!(system.solve(), None);
+    }
+}
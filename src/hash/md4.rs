@@ -0,0 +1,141 @@
+use super::{bytes_to_words, md_padding, words_to_bytes, Blocks, Hash};
+
+pub const INITIAL_STATE: [u32; 4] = [0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe, 0x1032_5476];
+
+pub const ROUND_2: u32 = 0x5a82_7999;
+pub const ROUND_3: u32 = 0x6ed9_eba1;
+
+/// A streaming MD4 hasher, as specified in RFC 1320.
+#[derive(Debug, Clone)]
+pub struct Md4 {
+    state: [u32; 4],
+    blocks: Blocks,
+}
+
+impl Hash for Md4 {
+    const BLOCK_SIZE: usize = 64;
+    const OUTPUT_SIZE: usize = 16;
+
+    fn new() -> Self {
+        Self::import_state(&words_to_bytes(&INITIAL_STATE, false), 0)
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        let state = &mut self.state;
+        self.blocks.feed(data, Self::BLOCK_SIZE, |block| {
+            compress(state, &bytes_to_words(block, false))
+        });
+    }
+
+    fn finalize(mut self) -> Vec<u8> {
+        self.update(&Self::padding(self.blocks.length));
+        words_to_bytes(&self.state, false)
+    }
+
+    fn export_state(&self) -> (Vec<u8>, u64) {
+        assert!(self.blocks.is_aligned(), "state taken mid-block");
+
+        (words_to_bytes(&self.state, false), self.blocks.length)
+    }
+
+    fn import_state(state: &[u8], length: u64) -> Self {
+        Self {
+            state: bytes_to_words(state, false),
+            blocks: Blocks::new(length, Self::BLOCK_SIZE),
+        }
+    }
+
+    fn padding(length: u64) -> Vec<u8> {
+        md_padding(length, Self::BLOCK_SIZE, 8, false)
+    }
+}
+
+pub fn f(x: u32, y: u32, z: u32) -> u32 {
+    (x & y) | (!x & z)
+}
+
+pub fn g(x: u32, y: u32, z: u32) -> u32 {
+    (x & y) | (x & z) | (y & z)
+}
+
+pub fn h(x: u32, y: u32, z: u32) -> u32 {
+    x ^ y ^ z
+}
+
+/// The compression function on a block of 16 little-endian words.
+pub fn compress(state: &mut [u32; 4], m: &[u32; 16]) {
+    let [mut a, mut b, mut c, mut d] = *state;
+
+    for i in [0, 4, 8, 12] {
+        a = a.wrapping_add(f(b, c, d)).wrapping_add(m[i]).rotate_left(3);
+        d = d
+            .wrapping_add(f(a, b, c))
+            .wrapping_add(m[i + 1])
+            .rotate_left(7);
+        c = c
+            .wrapping_add(f(d, a, b))
+            .wrapping_add(m[i + 2])
+            .rotate_left(11);
+        b = b
+            .wrapping_add(f(c, d, a))
+            .wrapping_add(m[i + 3])
+            .rotate_left(19);
+    }
+
+    let round_2 = |x: u32, y: u32, z: u32, w: u32, word: u32, shift: u32| {
+        x.wrapping_add(g(y, z, w))
+            .wrapping_add(word)
+            .wrapping_add(ROUND_2)
+            .rotate_left(shift)
+    };
+
+    for i in 0..4 {
+        a = round_2(a, b, c, d, m[i], 3);
+        d = round_2(d, a, b, c, m[i + 4], 5);
+        c = round_2(c, d, a, b, m[i + 8], 9);
+        b = round_2(b, c, d, a, m[i + 12], 13);
+    }
+
+    let round_3 = |x: u32, y: u32, z: u32, w: u32, word: u32, shift: u32| {
+        x.wrapping_add(h(y, z, w))
+            .wrapping_add(word)
+            .wrapping_add(ROUND_3)
+            .rotate_left(shift)
+    };
+
+    for i in [0, 2, 1, 3] {
+        a = round_3(a, b, c, d, m[i], 3);
+        d = round_3(d, a, b, c, m[i + 8], 9);
+        c = round_3(c, d, a, b, m[i + 4], 11);
+        b = round_3(b, c, d, a, m[i + 12], 15);
+    }
+
+    for (word, value) in state.iter_mut().zip([a, b, c, d]) {
+        *word = word.wrapping_add(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn md4_matches_rfc_1320_vectors() {
+        let vectors: [(&[u8], &str); 4] = [
+            (b"", "31d6cfe0d16ae931b73c59d7e0c089c0"),
+            (b"abc", "a448017aaf21d8525fc10ae87aa6729d"),
+            (
+                b"abcdefghijklmnopqrstuvwxyz",
+                "d79e1c308aa5bbcdeea8ed63df412da9",
+            ),
+            (
+                b"12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+                "e33b4ddc9c38f2199c3e7b164fcc0536",
+            ),
+        ];
+
+        for (message, digest) in vectors {
+            assert_eq!(hex::encode(Md4::digest(message)), digest);
+        }
+    }
+}
@@ -0,0 +1,169 @@
+pub mod md4;
+pub mod md5;
+pub mod sha1;
+pub mod sha2;
+
+/// A Merkle–Damgård hash function, processing the message a block at a time
+/// and outputting its chaining state after the final, padded block.
+pub trait Hash: Clone {
+    const BLOCK_SIZE: usize;
+    const OUTPUT_SIZE: usize;
+
+    fn new() -> Self;
+
+    fn update(&mut self, data: &[u8]);
+
+    fn finalize(self) -> Vec<u8>;
+
+    /// The chaining state, encoded like a digest, and the number of bytes
+    /// processed. Only available at a block boundary.
+    fn export_state(&self) -> (Vec<u8>, u64);
+
+    /// Resume hashing from an exported state, or from a digest, after
+    /// `length` bytes; `length` must be a whole number of blocks.
+    fn import_state(state: &[u8], length: u64) -> Self;
+
+    /// The padding appended to a message of `length` bytes.
+    fn padding(length: u64) -> Vec<u8>;
+
+    fn digest(data: &[u8]) -> Vec<u8> {
+        let mut hash = Self::new();
+        hash.update(data);
+        hash.finalize()
+    }
+}
+
+/// The bytes of a message not yet filling a block, and the running length.
+#[derive(Debug, Clone)]
+struct Blocks {
+    buffer: Vec<u8>,
+    length: u64,
+}
+
+impl Blocks {
+    fn new(length: u64, block_size: usize) -> Self {
+        assert!(
+            length.is_multiple_of(block_size as u64),
+            "length must be a whole number of blocks"
+        );
+
+        Self {
+            buffer: Vec::with_capacity(block_size),
+            length,
+        }
+    }
+
+    fn is_aligned(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    /// Buffer `data`, passing every completed block to `compress`.
+    fn feed<F: FnMut(&[u8])>(&mut self, data: &[u8], block_size: usize, mut compress: F) {
+        self.length += data.len() as u64;
+        self.buffer.extend_from_slice(data);
+
+        let whole = self.buffer.len() - self.buffer.len() % block_size;
+
+        for block in self.buffer[..whole].chunks_exact(block_size) {
+            compress(block);
+        }
+
+        self.buffer.drain(..whole);
+    }
+}
+
+/// `0x80`, zeros, and the bit length in a `length_size`-byte field, filling
+/// out the last block of a message of `length` bytes.
+fn md_padding(length: u64, block_size: usize, length_size: usize, big_endian: bool) -> Vec<u8> {
+    let used = (length % block_size as u64) as usize;
+    let zeros = (2 * block_size - used - 1 - length_size) % block_size;
+    let bits = (length as u128).wrapping_mul(8);
+
+    let mut padding = vec![0x80];
+    padding.extend(vec![0; zeros]);
+
+    if big_endian {
+        padding.extend(&bits.to_be_bytes()[16 - length_size..]);
+    } else {
+        padding.extend(&bits.to_le_bytes()[..length_size]);
+    }
+
+    padding
+}
+
+/// Encode 32-bit words as bytes, big-endian or not.
+fn words_to_bytes(words: &[u32], big_endian: bool) -> Vec<u8> {
+    words
+        .iter()
+        .flat_map(|word| {
+            if big_endian {
+                word.to_be_bytes()
+            } else {
+                word.to_le_bytes()
+            }
+        })
+        .collect()
+}
+
+fn bytes_to_words<const N: usize>(bytes: &[u8], big_endian: bool) -> [u32; N] {
+    assert_eq!(bytes.len(), 4 * N, "state has the wrong size");
+
+    let mut words = [0; N];
+
+    for (word, chunk) in words.iter_mut().zip(bytes.chunks_exact(4)) {
+        let chunk = chunk.try_into().unwrap();
+
+        *word = if big_endian {
+            u32::from_be_bytes(chunk)
+        } else {
+            u32::from_le_bytes(chunk)
+        };
+    }
+
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_resume<H: Hash>() {
+        let message = [0x5a; 300];
+
+        let mut hash = H::new();
+        hash.update(&message[..2 * H::BLOCK_SIZE]);
+
+        let (state, length) = hash.export_state();
+        let mut resumed = H::import_state(&state, length);
+        resumed.update(&message[2 * H::BLOCK_SIZE..]);
+
+        assert_eq!(resumed.finalize(), H::digest(&message));
+    }
+
+    fn check_padding<H: Hash>() {
+        for length in [0, 1, 55, 56, 63, 64, 111, 112, 127, 128, 1000] {
+            let padded = length + H::padding(length).len() as u64;
+
+            assert!(padded.is_multiple_of(H::BLOCK_SIZE as u64));
+            assert!(padded - length <= H::BLOCK_SIZE as u64 + 17);
+        }
+    }
+
+    #[test]
+    fn hashes_resume_from_exported_state() {
+        check_resume::<sha1::Sha1>();
+        check_resume::<md4::Md4>();
+        check_resume::<md5::Md5>();
+        check_resume::<sha2::Sha256>();
+        check_resume::<sha2::Sha512>();
+    }
+
+    #[test]
+    fn hashes_pad_to_whole_blocks() {
+        check_padding::<sha1::Sha1>();
+        check_padding::<md4::Md4>();
+        check_padding::<md5::Md5>();
+        check_padding::<sha2::Sha256>();
+        check_padding::<sha2::Sha512>();
+    }
+}
@@ -0,0 +1,125 @@
+use super::{bytes_to_words, md_padding, words_to_bytes, Blocks, Hash};
+
+const INITIAL_STATE: [u32; 5] = [
+    0x6745_2301,
+    0xefcd_ab89,
+    0x98ba_dcfe,
+    0x1032_5476,
+    0xc3d2_e1f0,
+];
+
+/// A streaming SHA-1 hasher.
+#[derive(Debug, Clone)]
+pub struct Sha1 {
+    state: [u32; 5],
+    blocks: Blocks,
+}
+
+impl Hash for Sha1 {
+    const BLOCK_SIZE: usize = 64;
+    const OUTPUT_SIZE: usize = 20;
+
+    fn new() -> Self {
+        Self::import_state(&words_to_bytes(&INITIAL_STATE, true), 0)
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        let state = &mut self.state;
+        self.blocks
+            .feed(data, Self::BLOCK_SIZE, |block| compress(state, block));
+    }
+
+    fn finalize(mut self) -> Vec<u8> {
+        self.update(&Self::padding(self.blocks.length));
+        words_to_bytes(&self.state, true)
+    }
+
+    fn export_state(&self) -> (Vec<u8>, u64) {
+        assert!(self.blocks.is_aligned(), "state taken mid-block");
+
+        (words_to_bytes(&self.state, true), self.blocks.length)
+    }
+
+    fn import_state(state: &[u8], length: u64) -> Self {
+        Self {
+            state: bytes_to_words(state, true),
+            blocks: Blocks::new(length, Self::BLOCK_SIZE),
+        }
+    }
+
+    fn padding(length: u64) -> Vec<u8> {
+        md_padding(length, Self::BLOCK_SIZE, 8, true)
+    }
+}
+
+fn compress(state: &mut [u32; 5], block: &[u8]) {
+    let mut w = [0; 80];
+
+    for (i, chunk) in block.chunks_exact(4).enumerate() {
+        w[i] = u32::from_be_bytes(chunk.try_into().unwrap());
+    }
+
+    for i in 16..80 {
+        w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e] = *state;
+
+    for (i, word) in w.iter().enumerate() {
+        let (f, k) = match i {
+            0..=19 => ((b & c) | (!b & d), 0x5a82_7999),
+            20..=39 => (b ^ c ^ d, 0x6ed9_eba1),
+            40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1b_bcdc),
+            _ => (b ^ c ^ d, 0xca62_c1d6),
+        };
+
+        let temp = a
+            .rotate_left(5)
+            .wrapping_add(f)
+            .wrapping_add(e)
+            .wrapping_add(k)
+            .wrapping_add(*word);
+
+        e = d;
+        d = c;
+        c = b.rotate_left(30);
+        b = a;
+        a = temp;
+    }
+
+    for (word, value) in state.iter_mut().zip([a, b, c, d, e]) {
+        *word = word.wrapping_add(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sha1_matches_fips_180_vectors() {
+        let vectors: [(&[u8], &str); 3] = [
+            (b"abc", "a9993e364706816aba3e25717850c26c9cd0d89d"),
+            (b"", "da39a3ee5e6b4b0d3255bfef95601890afd80709"),
+            (
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                "84983e441c3bd26ebaae4aa1f95129e5e54670f1",
+            ),
+        ];
+
+        for (message, digest) in vectors {
+            assert_eq!(hex::encode(Sha1::digest(message)), digest);
+        }
+
+        let mut sha1 = Sha1::new();
+
+        for _ in 0..1000 {
+            sha1.update(&[b'a'; 1000]);
+        }
+
+        assert_eq!(
+            hex::encode(sha1.finalize()),
+            "34aa973cd4c4daa4f61eeb2bdbad27316534016f"
+        );
+    }
+}
@@ -1,7 +1,5 @@
-use std::collections::HashMap;
-
-#[derive(Debug)]
-pub struct KeyValue(HashMap<String, String>);
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyValue(Vec<(String, String)>);
 
 impl KeyValue {
     pub fn profile_for(email: &str) -> Self {
@@ -10,23 +8,28 @@ impl KeyValue {
             .filter(|ch| *ch != '=' && *ch != '&')
             .collect();
 
-        Self(HashMap::from([
+        Self(vec![
             (String::from("email"), email),
             (String::from("uid"), String::from("10")),
             (String::from("role"), String::from("user")),
-        ]))
+        ])
     }
 
     pub fn decode(encoding: &str) -> Self {
+        Self::decode_with(encoding, '&')
+    }
+
+    /// Decode pairs split by `separator`, skipping empty segments and taking
+    /// a segment without `=` as a key with an empty value, since tampered
+    /// cookies decode to anything.
+    pub fn decode_with(encoding: &str, separator: char) -> Self {
         Self(
             encoding
-                .split('&')
+                .split(separator)
+                .filter(|pair| !pair.is_empty())
                 .map(|pair| {
-                    let mut kv = pair.split('=');
-                    (
-                        String::from(kv.next().expect("no key")),
-                        String::from(kv.next().expect("no value")),
-                    )
+                    let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                    (String::from(key), String::from(value))
                 })
                 .collect(),
         )
@@ -39,4 +42,11 @@ impl KeyValue {
             .collect::<Vec<String>>()
             .join("&")
     }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
 }
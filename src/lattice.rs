@@ -0,0 +1,208 @@
+/// How much shorter each LLL basis vector must keep its Gram-Schmidt length
+/// relative to the one before it.
+const DELTA: f64 = 0.99;
+
+/// The slack allowed over 1/2 in size-reduced Gram-Schmidt coefficients, so
+/// that rounding errors cannot make size reduction bounce forever.
+const ETA: f64 = 0.51;
+
+/// An LLL-reduced lattice basis with its Gram-Schmidt orthogonalisation.
+///
+/// The basis vectors are exact; the orthogonalisation is floating point and
+/// is recomputed from them whenever they change, which is accurate enough
+/// for the small lattices of 60-odd-bit entries that generator attacks need.
+#[derive(Debug, Clone)]
+pub struct Lattice {
+    basis: Vec<Vec<i128>>,
+    mu: Vec<Vec<f64>>,
+    norms: Vec<f64>,
+}
+
+impl Lattice {
+    /// LLL-reduce the lattice spanned by the rows of `basis`, which must be
+    /// linearly independent.
+    pub fn reduce(basis: Vec<Vec<i128>>) -> Self {
+        let size = basis.len();
+        let mut lattice = Self {
+            basis,
+            mu: vec![vec![0.0; size]; size],
+            norms: vec![0.0; size],
+        };
+
+        if size == 0 {
+            return lattice;
+        }
+
+        lattice.orthogonalise(0);
+
+        let mut k = 1;
+
+        while k < size {
+            lattice.size_reduce(k);
+
+            let mu = lattice.mu[k][k - 1];
+
+            if lattice.norms[k] >= (DELTA - mu * mu) * lattice.norms[k - 1] {
+                k += 1;
+            } else {
+                lattice.basis.swap(k - 1, k);
+
+                if k > 1 {
+                    k -= 1;
+                } else {
+                    lattice.orthogonalise(0);
+                }
+            }
+        }
+
+        lattice
+    }
+
+    pub fn basis(&self) -> &[Vec<i128>] {
+        &self.basis
+    }
+
+    /// A lattice vector close to `target`, by Babai's nearest plane method.
+    /// It is the closest one whenever `target` is much nearer to it than the
+    /// reduced basis vectors are long.
+    pub fn closest_vector(&self, target: &[i128]) -> Vec<i128> {
+        let mut residual = target.to_vec();
+
+        // The components of the target along the orthogonalised basis.
+        let mut projections = Vec::with_capacity(self.basis.len());
+
+        for (j, vector) in self.basis.iter().enumerate() {
+            let projection = (0..j).fold(dot(&residual, vector), |acc, i| {
+                acc - self.mu[j][i] * projections[i]
+            });
+
+            projections.push(projection);
+        }
+
+        for (j, vector) in self.basis.iter().enumerate().rev() {
+            let coefficient = (projections[j] / self.norms[j]).round();
+
+            if coefficient != 0.0 {
+                for (a, b) in residual.iter_mut().zip(vector) {
+                    *a -= coefficient as i128 * b;
+                }
+
+                for (i, projection) in projections[..j].iter_mut().enumerate() {
+                    *projection -= coefficient * self.mu[j][i] * self.norms[i];
+                }
+            }
+        }
+
+        target.iter().zip(&residual).map(|(a, b)| a - b).collect()
+    }
+
+    /// Recompute row `k` of the orthogonalisation, assuming the rows before
+    /// it are current.
+    fn orthogonalise(&mut self, k: usize) {
+        let mut norm = dot(&self.basis[k], &self.basis[k]);
+
+        for j in 0..k {
+            let product = (0..j).fold(dot(&self.basis[k], &self.basis[j]), |acc, i| {
+                acc - self.mu[j][i] * self.mu[k][i] * self.norms[i]
+            });
+
+            self.mu[k][j] = product / self.norms[j];
+            norm -= self.mu[k][j] * product;
+        }
+
+        self.norms[k] = norm;
+    }
+
+    /// Subtract multiples of the earlier basis vectors from vector `k` until
+    /// its Gram-Schmidt coefficients are all at most about 1/2.
+    fn size_reduce(&mut self, k: usize) {
+        loop {
+            self.orthogonalise(k);
+
+            let mut changed = false;
+
+            for j in (0..k).rev() {
+                if self.mu[k][j].abs() <= ETA {
+                    continue;
+                }
+
+                let quotient = self.mu[k][j].round();
+                let (head, tail) = self.basis.split_at_mut(k);
+
+                for (a, b) in tail[0].iter_mut().zip(&head[j]) {
+                    *a -= quotient as i128 * b;
+                }
+
+                for i in 0..j {
+                    self.mu[k][i] -= quotient * self.mu[j][i];
+                }
+
+                self.mu[k][j] -= quotient;
+                changed = true;
+            }
+
+            if !changed {
+                return;
+            }
+        }
+    }
+}
+
+fn dot(one: &[i128], two: &[i128]) -> f64 {
+    one.iter()
+        .zip(two)
+        .map(|(a, b)| *a as f64 * *b as f64)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reduction_finds_short_vectors() {
+        // The multiples of (1, 1000) modulo 1009, of which (1, -9) is the
+        // shortest.
+        let lattice = Lattice::reduce(vec![vec![1, 1000], vec![0, 1009]]);
+        let shortest = &lattice.basis()[0];
+
+        assert_eq!(shortest[0].abs(), 1);
+        assert_eq!(shortest[1].abs(), 9);
+        assert_eq!(shortest[0].signum(), -shortest[1].signum());
+    }
+
+    #[test]
+    fn closest_vector_undoes_small_errors() {
+        let modulus = 1 << 48;
+        let multiplier: i128 = 0x5_deec_e66d;
+        let basis = (0..6)
+            .map(|i| {
+                let mut row = vec![0; 6];
+
+                if i == 0 {
+                    (0..6).fold(1, |power, j| {
+                        row[j] = power;
+                        power * multiplier % modulus
+                    });
+                } else {
+                    row[i] = modulus;
+                }
+
+                row
+            })
+            .collect::<Vec<Vec<i128>>>();
+
+        let point = basis[0]
+            .iter()
+            .zip(&basis[3])
+            .map(|(a, b)| 0x1234_5678 * a - 3 * b)
+            .collect::<Vec<i128>>();
+        let noisy = point
+            .iter()
+            .zip([5, -3, 1 << 20, 0, -77, 1 << 19])
+            .map(|(a, b)| a + b)
+            .collect::<Vec<i128>>();
+
+        assert_eq!(Lattice::reduce(basis).closest_vector(&noisy), point);
+    }
+}
@@ -0,0 +1,86 @@
+use crate::hash::Hash;
+use std::ops::Range;
+
+/// A message with a valid secret-prefix MAC, assuming the key length guessed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Forgery {
+    pub key_len: usize,
+    pub message: Vec<u8>,
+    pub mac: Vec<u8>,
+}
+
+/// Forge MACs for `message || padding || suffix` from the MAC of `message`,
+/// one per key length in `key_len_range`.
+///
+/// A Merkle–Damgård digest is the chaining state after the padded message,
+/// so hashing can resume from it. The glue padding depends on the length of
+/// the key, which has to be guessed; only the right guess verifies.
+pub fn length_extend<H: Hash>(
+    mac: &[u8],
+    message: &[u8],
+    suffix: &[u8],
+    key_len_range: Range<usize>,
+) -> Vec<Forgery> {
+    key_len_range
+        .map(|key_len| {
+            let length = (key_len + message.len()) as u64;
+            let glue = H::padding(length);
+
+            let mut hash = H::import_state(mac, length + glue.len() as u64);
+            hash.update(suffix);
+
+            let mut forged = message.to_vec();
+            forged.extend(&glue);
+            forged.extend(suffix);
+
+            Forgery {
+                key_len,
+                message: forged,
+                mac: hash.finalize(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cookie;
+    use crate::hash::{md4::Md4, md5::Md5, sha1::Sha1, sha2::Sha256};
+    use crate::mac::SecretPrefixMac;
+    use crate::random::{ChaCha20Rng, TEST_SEED};
+
+    fn check_length_extension<H: Hash>() {
+        let service = SecretPrefixMac::<H>::new(&mut ChaCha20Rng::seed_from_u64(TEST_SEED));
+        let message = cookie::encode_userdata("foo");
+        let mac = service.sign(message.as_bytes());
+
+        let forgery = length_extend::<H>(&mac, message.as_bytes(), b";admin=true;", 0..64)
+            .into_iter()
+            .find(|forgery| service.verify(&forgery.message, &forgery.mac))
+            .expect("no key length verified");
+
+        assert!(forgery.message.starts_with(message.as_bytes()));
+        assert!(cookie::is_admin(&forgery.message));
+    }
+
+    #[test]
+    fn length_extension_forges_sha1() {
+        check_length_extension::<Sha1>();
+    }
+
+    #[test]
+    fn length_extension_forges_md4() {
+        check_length_extension::<Md4>();
+    }
+
+    #[test]
+    fn length_extension_forges_md5() {
+        check_length_extension::<Md5>();
+    }
+
+    #[test]
+    fn length_extension_forges_sha256() {
+        check_length_extension::<Sha256>();
+    }
+}
@@ -0,0 +1,343 @@
+pub mod base64;
+pub mod byte_at_a_time;
+pub mod clock;
+pub mod cookie;
+pub mod crib_drag;
+pub mod crypto;
+pub mod ctr_edit;
+pub mod fixed_nonce;
+pub mod gf2;
+pub mod gliding_slice;
+pub mod hash;
+pub mod key_value;
+pub mod lattice;
+pub mod length_extension;
+pub mod mac;
+pub mod md4_collision;
+pub mod mt19937;
+pub mod mt19937_cipher;
+pub mod mt19937_partial;
+pub mod padding_oracle;
+pub mod profile_service;
+pub mod random;
+pub mod result_keeper;
+pub mod score;
+pub mod stats;
+pub mod timing_attack;
+pub mod timing_server;
+
+use lazy_static::lazy_static;
+use std::convert;
+use std::fmt;
+
+use gliding_slice::GlidingSlice;
+use random::RandomSource;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Buffer {
+    bytes: Vec<u8>,
+}
+
+impl Buffer {
+    pub fn new(bytes: &[u8]) -> Self {
+        Self {
+            bytes: bytes.to_vec(),
+        }
+    }
+
+    pub fn from_hex(hex: &str) -> Self {
+        Self {
+            bytes: hex::decode(hex).expect("invalid hex input"),
+        }
+    }
+
+    pub fn from_base64(base64: &str) -> Self {
+        Self {
+            bytes: base64::decode(base64),
+        }
+    }
+
+    pub fn pad(&mut self, buffer_size: usize) {
+        if buffer_size > self.bytes.len() {
+            let pad_size = buffer_size - self.bytes.len();
+            self.bytes.extend(vec![pad_size as u8; pad_size]);
+        }
+    }
+
+    pub fn pad_to_multiple(&mut self, block_size: usize) {
+        let pad_size = block_size - self.bytes.len() % block_size;
+        self.bytes.extend(vec![pad_size as u8; pad_size]);
+    }
+
+    pub fn has_valid_padding(&self, block_size: usize) -> bool {
+        let last = self.bytes.last().copied().unwrap_or_default() as usize;
+
+        if last == 0 || last > block_size || last > self.bytes.len() {
+            return false;
+        }
+
+        self.bytes
+            .iter()
+            .rev()
+            .take(last)
+            .all(|byte| *byte as usize == last)
+    }
+
+    pub fn unpad(&mut self) {
+        let last = self.bytes.last().copied().unwrap_or_default();
+
+        let count = self
+            .bytes
+            .iter()
+            .rev()
+            .take_while(|byte| **byte == last)
+            .count();
+
+        if count == last as usize {
+            self.bytes.truncate(self.bytes.len() - count);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn xor<T: AsRef<[u8]>>(&self, key: T) -> Self {
+        Self {
+            bytes: self
+                .bytes
+                .iter()
+                .zip(key.as_ref().iter().cycle())
+                .map(|(a, b)| a ^ b)
+                .collect(),
+        }
+    }
+
+    pub fn xor_repeating_key_search(&self, size: usize) -> Option<f64> {
+        if size > self.bytes.len() / 2 {
+            return None;
+        }
+
+        let (chunks, distances) =
+            GlidingSlice::new(&self.bytes, size).fold((1, 0.0), |(chunks, acc), (one, two)| {
+                let distance = hamming_distance(one, two) as f64 / size as f64;
+                (chunks + 1, acc + distance)
+            });
+
+        Some(distances / chunks as f64)
+    }
+
+    pub fn transpose(&self, size: usize) -> Vec<Self> {
+        let size = std::cmp::min(size, self.bytes.len());
+
+        let mut rows = vec![
+            Self {
+                bytes: Vec::with_capacity(self.bytes.len() / size)
+            };
+            size
+        ];
+
+        for (row, byte) in self
+            .bytes
+            .iter()
+            .enumerate()
+            .map(|(i, byte)| (i % size, byte))
+        {
+            rows[row].bytes.push(*byte);
+        }
+
+        rows
+    }
+
+    pub fn count_identical_runs(&self, run_length: usize) -> usize {
+        if run_length > self.bytes.len() {
+            return 0;
+        }
+
+        let mut runs = 0;
+
+        for start in 0..self.bytes.len() - run_length - 2 {
+            for compare_start in start + 1..self.bytes.len() - run_length - 1 {
+                let template = &self.bytes[start..start + run_length];
+                let compare = &self.bytes[compare_start..compare_start + run_length];
+
+                if template == compare {
+                    runs += 1;
+                }
+            }
+        }
+
+        runs
+    }
+
+    pub fn as_hex(&self) -> String {
+        hex::encode(&self.bytes)
+    }
+
+    pub fn as_base64(&self) -> String {
+        base64::encode(&self.bytes)
+    }
+
+    pub fn aes_128_ecb_encrypt(&self, key: &[u8]) -> Self {
+        Self {
+            bytes: crypto::aes_128_ecb_encrypt(key, &self.bytes),
+        }
+    }
+
+    pub fn aes_128_ecb_decrypt(&self, key: &[u8]) -> Self {
+        Self {
+            bytes: crypto::aes_128_ecb_decrypt(key, &self.bytes),
+        }
+    }
+
+    pub fn aes_128_ctr(&self, key: &[u8], layout: crypto::CtrLayout) -> Self {
+        Self {
+            bytes: crypto::aes_128_ctr(key, layout, &self.bytes),
+        }
+    }
+
+    pub fn aes_128_cbc_encrypt(&self, key: &[u8], iv: &[u8]) -> Self {
+        let plaintext_blocks = self
+            .bytes
+            .chunks(16)
+            .map(|bytes| {
+                let mut block = Self::new(bytes);
+                block.pad(16);
+                block
+            })
+            .collect::<Vec<_>>();
+
+        let ecb = openssl::symm::Cipher::aes_128_ecb();
+
+        let mut last_cipher_block = Self::new(iv);
+        let mut ciphertext = Vec::with_capacity(16 * plaintext_blocks.len());
+
+        for block in plaintext_blocks.into_iter() {
+            let combined = block.xor(last_cipher_block);
+            let mut encrypted = openssl::symm::encrypt(ecb, key, None, combined.as_ref())
+                .expect("failed to encrypt");
+
+            encrypted.truncate(16);
+
+            ciphertext.extend(&encrypted);
+            last_cipher_block = Self { bytes: encrypted };
+        }
+
+        Self { bytes: ciphertext }
+    }
+
+    pub fn aes_128_cbc_decrypt(&self, key: &[u8], iv: &[u8]) -> Self {
+        assert!(self.bytes.len().is_multiple_of(16), "padding error");
+
+        let ciphertext_blocks = self
+            .bytes
+            .chunks_exact(16)
+            .map(Self::new)
+            .collect::<Vec<_>>();
+
+        let mut crypter = openssl::symm::Crypter::new(
+            openssl::symm::Cipher::aes_128_ecb(),
+            openssl::symm::Mode::Decrypt,
+            key,
+            None,
+        )
+        .unwrap();
+
+        crypter.pad(false);
+
+        let mut last_cipher_block = Self::new(iv);
+        let mut buffer = [0; 32];
+        let mut cleartext = Vec::with_capacity(self.bytes.len());
+
+        for block in ciphertext_blocks.into_iter() {
+            crypter
+                .update(block.as_ref(), &mut buffer)
+                .expect("failed to decrypt");
+
+            let combined = Self::new(&buffer[0..16]);
+            let decrypted = combined.xor(last_cipher_block);
+
+            cleartext.extend(decrypted.as_ref());
+            last_cipher_block = block;
+        }
+
+        Self { bytes: cleartext }
+    }
+}
+
+impl convert::AsRef<[u8]> for Buffer {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl fmt::Display for Buffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", String::from_utf8_lossy(&self.bytes))
+    }
+}
+
+pub fn best_one_byte_xor(buffer: &Buffer) -> (u8, f64) {
+    let mut best_key = 0u8;
+    let mut best_penalty = score::english_text_frequency(buffer.xor([0]).as_ref());
+
+    for k in 1..=u8::MAX {
+        let text = buffer.xor([k]);
+        let penalty = score::english_text_frequency(text.as_ref());
+
+        if penalty < best_penalty {
+            best_penalty = penalty;
+            best_key = k;
+        }
+    }
+
+    (best_key, best_penalty)
+}
+
+fn hamming_distance(one: &[u8], two: &[u8]) -> usize {
+    one.iter()
+        .zip(two.iter())
+        .map(|(a, b)| (a ^ b).count_ones() as usize)
+        .sum()
+}
+
+lazy_static! {
+    static ref SECRET: Buffer = Buffer::from_base64(
+        "Um9sbGluJyBpbiBteSA1LjAKV2l0aCBteSByYWctdG9wIGRvd24gc28gbXkg\
+        aGFpciBjYW4gYmxvdwpUaGUgZ2lybGllcyBvbiBzdGFuZGJ5IHdhdmluZyBq\
+        dXN0IHRvIHNheSBoaQpEaWQgeW91IHN0b3A/IE5vLCBJIGp1c3QgZHJvdmUg\
+        YnkK",
+    );
+}
+
+pub struct EncryptionOracle {
+    key: Vec<u8>,
+}
+
+impl EncryptionOracle {
+    pub fn new(random: &mut dyn RandomSource) -> Self {
+        Self {
+            key: random.bytes(16),
+        }
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> Buffer {
+        Buffer {
+            bytes: openssl::symm::encrypt(
+                openssl::symm::Cipher::aes_128_ecb(),
+                &self.key,
+                None,
+                &plaintext
+                    .iter()
+                    .copied()
+                    .chain(SECRET.bytes.iter().copied())
+                    .collect::<Vec<u8>>(),
+            )
+            .unwrap(),
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use crate::hash::Hash;
+use crate::random::RandomSource;
+use std::hint;
+use std::marker::PhantomData;
+
+/// `H(key || message)`, the naive MAC that length extension breaks.
+pub fn secret_prefix_mac<H: Hash>(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut hash = H::new();
+    hash.update(key);
+    hash.update(message);
+    hash.finalize()
+}
+
+/// A service signing messages with a secret-prefix MAC under a key of
+/// secret length.
+pub struct SecretPrefixMac<H> {
+    key: Vec<u8>,
+    hash: PhantomData<H>,
+}
+
+impl<H: Hash> SecretPrefixMac<H> {
+    pub fn new(random: &mut dyn RandomSource) -> Self {
+        let length = 1 + random.below(32) as usize;
+
+        Self {
+            key: random.bytes(length),
+            hash: PhantomData,
+        }
+    }
+
+    pub fn sign(&self, message: &[u8]) -> Vec<u8> {
+        secret_prefix_mac::<H>(&self.key, message)
+    }
+
+    pub fn verify(&self, message: &[u8], mac: &[u8]) -> bool {
+        constant_time_eq(&self.sign(message), mac)
+    }
+}
+
+/// Compare two tags in time depending only on their lengths.
+pub fn constant_time_eq(one: &[u8], two: &[u8]) -> bool {
+    if one.len() != two.len() {
+        return false;
+    }
+
+    let difference = one
+        .iter()
+        .zip(two)
+        .fold(0, |difference, (a, b)| difference | (a ^ b));
+
+    hint::black_box(difference) == 0
+}
+
+/// HMAC over any hash function, as specified in RFC 2104.
+#[derive(Debug, Clone)]
+pub struct Hmac<H> {
+    inner: H,
+    outer: H,
+}
+
+impl<H: Hash> Hmac<H> {
+    pub fn new(key: &[u8]) -> Self {
+        let mut block = if key.len() > H::BLOCK_SIZE {
+            H::digest(key)
+        } else {
+            key.to_vec()
+        };
+
+        block.resize(H::BLOCK_SIZE, 0);
+
+        let pad = |byte: u8| {
+            let mut hash = H::new();
+            hash.update(&block.iter().map(|key| key ^ byte).collect::<Vec<u8>>());
+            hash
+        };
+
+        Self {
+            inner: pad(0x36),
+            outer: pad(0x5c),
+        }
+    }
+
+    pub fn mac(key: &[u8], message: &[u8]) -> Vec<u8> {
+        let mut hmac = Self::new(key);
+        hmac.update(message);
+        hmac.finalize()
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    pub fn finalize(self) -> Vec<u8> {
+        let mut outer = self.outer;
+        outer.update(&self.inner.finalize());
+        outer.finalize()
+    }
+
+    /// Whether `tag` is the HMAC of everything passed to `update`, compared
+    /// in constant time.
+    pub fn verify(self, tag: &[u8]) -> bool {
+        constant_time_eq(&self.finalize(), tag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::{md5::Md5, sha1::Sha1, sha2::Sha256, sha2::Sha512};
+
+    const LARGE_KEY_MESSAGE: &[u8] = b"Test Using Larger Than Block-Size Key - Hash Key First";
+
+    #[test]
+    fn hmac_matches_rfc_2202() {
+        // The first case uses a key as long as the digest.
+        assert_eq!(
+            hex::encode(Hmac::<Md5>::mac(&[0x0b; 16], b"Hi There")),
+            "9294727a3638bb1c13f48ef8158bfc9d"
+        );
+        assert_eq!(
+            hex::encode(Hmac::<Sha1>::mac(&[0x0b; 20], b"Hi There")),
+            "b617318655057264e28bc0b6fb378c8ef146be00"
+        );
+
+        let cases: [(Vec<u8>, &[u8], &str, &str); 2] = [
+            (
+                b"Jefe".to_vec(),
+                b"what do ya want for nothing?",
+                "750c783e6ab0b503eaa86e310a5db738",
+                "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79",
+            ),
+            (
+                vec![0xaa; 80],
+                LARGE_KEY_MESSAGE,
+                "6b1ab7fe4bd7bf8f0b62e6ce61b9d0cd",
+                "aa4ae5e15272d00e95705637ce8a3b55ed402112",
+            ),
+        ];
+
+        for (key, message, md5, sha1) in cases {
+            assert_eq!(hex::encode(Hmac::<Md5>::mac(&key, message)), md5);
+            assert_eq!(hex::encode(Hmac::<Sha1>::mac(&key, message)), sha1);
+        }
+    }
+
+    #[test]
+    fn hmac_matches_rfc_4231() {
+        let cases: [(Vec<u8>, &[u8], &str, &str); 3] = [
+            (
+                vec![0x0b; 20],
+                b"Hi There",
+                "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+                "87aa7cdea5ef619d4ff0b4241a1d6cb02379f4e2ce4ec2787ad0b30545e17cde\
+                 daa833b7d6b8a702038b274eaea3f4e4be9d914eeb61f1702e696c203a126854",
+            ),
+            (
+                b"Jefe".to_vec(),
+                b"what do ya want for nothing?",
+                "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+                "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea250554\
+                 9758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737",
+            ),
+            (
+                vec![0xaa; 131],
+                LARGE_KEY_MESSAGE,
+                "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+                "80b24263c7c1a3ebb71493c1dd7be8b49b46d1f41b4aeec1121b013783f8f352\
+                 6b56d037e05f2598bd0fd2215d6a1e5295e64f73f63f0aec8b915a985d786598",
+            ),
+        ];
+
+        for (key, message, sha256, sha512) in cases {
+            assert_eq!(hex::encode(Hmac::<Sha256>::mac(&key, message)), sha256);
+            assert_eq!(hex::encode(Hmac::<Sha512>::mac(&key, message)), sha512);
+        }
+    }
+
+    #[test]
+    fn hmac_verifies_incremental_updates() {
+        let tag = Hmac::<Sha1>::mac(b"key", b"hello, world");
+
+        let mut hmac = Hmac::<Sha1>::new(b"key");
+        hmac.update(b"hello, ");
+        hmac.update(b"world");
+
+        assert!(hmac.clone().verify(&tag));
+        assert!(!hmac.clone().verify(&tag[..19]));
+
+        let mut forged = tag.clone();
+        forged[19] ^= 1;
+
+        assert!(!hmac.verify(&forged));
+    }
+}
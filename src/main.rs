@@ -1,296 +1,107 @@
-mod base64;
-mod crypto;
-mod gliding_slice;
-mod key_value;
-mod linux_random;
-mod result_keeper;
-mod score;
-
-use lazy_static::lazy_static;
-use std::convert;
-use std::fmt;
+use std::env;
 use std::fs;
-
-use gliding_slice::GlidingSlice;
-use key_value::KeyValue;
-use result_keeper::ResultKeeper;
-
-#[derive(Debug, Clone)]
-struct Buffer {
-    bytes: Vec<u8>,
-}
-
-impl Buffer {
-    fn new(bytes: &[u8]) -> Self {
-        Self {
-            bytes: bytes.to_vec(),
+use std::io;
+use std::net::TcpListener;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crypto_pals::crib_drag::{self, Workbench};
+use crypto_pals::fixed_nonce::{self, Tails};
+use crypto_pals::padding_oracle::{self, PaddingOracleService};
+use crypto_pals::random::{self, ChaCha20Rng, RandomSource};
+use crypto_pals::timing_attack::TimingAttack;
+use crypto_pals::timing_server::{HttpTarget, TimingServer};
+use crypto_pals::Buffer;
+
+/// Resume the crib-dragging session in `session`, or start one from a file
+/// of base64 ciphertexts seeded with the statistical keystream.
+fn crib_drag(session: &Path, ciphertexts: Option<&str>) -> io::Result<()> {
+    let mut workbench = match ciphertexts {
+        Some(path) => {
+            let ciphertexts = fs::read_to_string(path)?
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(Buffer::from_base64)
+                .collect::<Vec<Buffer>>();
+
+            let (keystream, _) =
+                fixed_nonce::break_fixed_nonce_ctr(&ciphertexts, Tails::ColumnByColumn);
+
+            let mut workbench = Workbench::new(ciphertexts);
+            workbench.seed(&keystream);
+            workbench
         }
-    }
 
-    fn from_hex(hex: &str) -> Self {
-        Self {
-            bytes: hex::decode(hex).expect("invalid hex input"),
-        }
-    }
-
-    fn from_base64(base64: &str) -> Self {
-        Self {
-            bytes: base64::decode(base64),
-        }
-    }
+        None => Workbench::load(session)?,
+    };
 
-    fn pad(&mut self, buffer_size: usize) {
-        if buffer_size > self.bytes.len() {
-            let pad_size = buffer_size - self.bytes.len();
-            self.bytes.extend(vec![pad_size as u8; pad_size]);
-        }
-    }
-
-    fn unpad(&mut self) {
-        let last = self.bytes.last().copied().unwrap_or_default();
-
-        let count = self
-            .bytes
-            .iter()
-            .rev()
-            .take_while(|byte| **byte == last)
-            .count();
+    crib_drag::repl(&mut workbench, session, io::stdin().lock(), io::stdout())
+}
 
-        if count == last as usize {
-            self.bytes.truncate(self.bytes.len() - count);
-        }
-    }
+/// Run the HMAC timing-leak server on a localhost port, sleeping `delay`
+/// per matching signature byte.
+fn serve(port: u16, delay: Duration) -> io::Result<()> {
+    let server = TimingServer::new(random_source().as_mut(), delay);
+    let listener = TcpListener::bind(("127.0.0.1", port))?;
 
-    fn len(&self) -> usize {
-        self.bytes.len()
-    }
+    println!("listening on http://{}/test", listener.local_addr()?);
 
-    fn xor<T: AsRef<[u8]>>(&self, key: T) -> Self {
-        Self {
-            bytes: self
-                .bytes
-                .iter()
-                .zip(key.as_ref().iter().cycle())
-                .map(|(a, b)| a ^ b)
-                .collect(),
-        }
-    }
-
-    fn xor_repeating_key_search(&self, size: usize) -> Option<f64> {
-        if size > self.bytes.len() / 2 {
-            return None;
-        }
-
-        let (chunks, distances) = GlidingSlice::new(&self.bytes, size).into_iter().fold(
-            (1, 0.0),
-            |(chunks, acc), (one, two)| {
-                let distance = hamming_distance(one, two) as f64 / size as f64;
-                (chunks + 1, acc + distance)
-            },
-        );
+    Arc::new(server).serve(listener)
+}
 
-        Some(distances / chunks as f64)
+/// The operating system's generator, or a replayable one when the `SEED`
+/// environment variable holds a number.
+fn random_source() -> Box<dyn RandomSource> {
+    match env::var("SEED").ok().and_then(|seed| seed.parse().ok()) {
+        Some(seed) => Box::new(ChaCha20Rng::seed_from_u64(seed)),
+        None => random::os().expect("no operating system randomness"),
     }
+}
 
-    fn transpose(&self, size: usize) -> Vec<Self> {
-        let size = std::cmp::min(size, self.bytes.len());
-
-        let mut rows = vec![
-            Self {
-                bytes: Vec::with_capacity(self.bytes.len() / size)
-            };
-            size
-        ];
+fn main() {
+    let args = env::args().collect::<Vec<String>>();
 
-        for (row, byte) in self
-            .bytes
-            .iter()
-            .enumerate()
-            .map(|(i, byte)| (i % size, byte))
-        {
-            rows[row].bytes.push(*byte);
+    match args.as_slice() {
+        [_, command, session, rest @ ..] if command == "crib" => {
+            crib_drag(Path::new(session), rest.first().map(String::as_str))
+                .expect("crib dragging failed");
+            return;
         }
 
-        rows
-    }
+        [_, command, port, rest @ ..] if command == "serve" => {
+            let delay = rest.first().map_or(Ok(50), |delay| delay.parse());
 
-    fn count_identical_runs(&self, run_length: usize) -> usize {
-        if run_length > self.bytes.len() {
-            return 0;
+            serve(
+                port.parse().expect("malformed port"),
+                Duration::from_millis(delay.expect("malformed delay")),
+            )
+            .expect("serving failed");
+            return;
         }
 
-        let mut runs = 0;
+        [_, command, address, file, ..] if command == "attack" => {
+            let target = HttpTarget::new(address.parse().expect("malformed address"));
+            let signature = TimingAttack::default()
+                .recover_signature(&target, file, 20)
+                .expect("attack failed");
 
-        for start in 0..self.bytes.len() - run_length - 2 {
-            for compare_start in start + 1..self.bytes.len() - run_length - 1 {
-                let template = &self.bytes[start..start + run_length];
-                let compare = &self.bytes[compare_start..compare_start + run_length];
-
-                if template == compare {
-                    runs += 1;
-                }
+            match signature {
+                Some(signature) => println!("{}", hex::encode(signature)),
+                None => println!("no valid signature found"),
             }
+            return;
         }
 
-        runs
-    }
-
-    fn as_hex(&self) -> String {
-        hex::encode(&self.bytes)
-    }
-
-    fn as_base64(&self) -> String {
-        base64::encode(&self.bytes)
-    }
-
-    fn aes_128_ecb_encrypt(&self, key: &[u8]) -> Self {
-        Self {
-            bytes: crypto::aes_128_ecb_encrypt(key, &self.bytes),
-        }
-    }
-
-    fn aes_128_ecb_decrypt(&self, key: &[u8]) -> Self {
-        Self {
-            bytes: crypto::aes_128_ecb_decrypt(key, &self.bytes),
-        }
-    }
-
-    fn aes_128_cbc_encrypt(&self, key: &[u8], iv: &[u8]) -> Self {
-        let plaintext_blocks = self
-            .bytes
-            .chunks(16)
-            .map(|bytes| {
-                let mut block = Self::new(bytes);
-                block.pad(16);
-                block
-            })
-            .collect::<Vec<_>>();
-
-        let ecb = openssl::symm::Cipher::aes_128_ecb();
-
-        let mut last_cipher_block = Self::new(iv);
-        let mut ciphertext = Vec::with_capacity(16 * plaintext_blocks.len());
-
-        for block in plaintext_blocks.into_iter() {
-            let combined = block.xor(last_cipher_block);
-            let mut encrypted = openssl::symm::encrypt(ecb, key, None, combined.as_ref())
-                .expect("failed to encrypt");
-
-            encrypted.truncate(16);
-
-            ciphertext.extend(&encrypted);
-            last_cipher_block = Self { bytes: encrypted };
-        }
-
-        Self { bytes: ciphertext }
+        _ => (),
     }
 
-    fn aes_128_cbc_decrypt(&self, key: &[u8], iv: &[u8]) -> Self {
-        assert!(self.bytes.len() % 16 == 0, "padding error");
-
-        let ciphertext_blocks = self
-            .bytes
-            .chunks_exact(16)
-            .map(Self::new)
-            .collect::<Vec<_>>();
-
-        let mut crypter = openssl::symm::Crypter::new(
-            openssl::symm::Cipher::aes_128_ecb(),
-            openssl::symm::Mode::Decrypt,
-            key,
-            None,
-        )
-        .unwrap();
-
-        crypter.pad(false);
+    let mut random = random_source();
+    let service = PaddingOracleService::new(random.as_mut());
+    let (iv, ciphertext) = service.encrypt(random.as_mut());
 
-        let mut last_cipher_block = Self::new(iv);
-        let mut buffer = [0; 32];
-        let mut cleartext = Vec::with_capacity(self.bytes.len());
-
-        for block in ciphertext_blocks.into_iter() {
-            crypter
-                .update(block.as_ref(), &mut buffer)
-                .expect("failed to decrypt");
-
-            let combined = Self::new(&buffer[0..16]);
-            let decrypted = combined.xor(last_cipher_block);
-
-            cleartext.extend(decrypted.as_ref());
-            last_cipher_block = block;
-        }
-
-        Self { bytes: cleartext }
-    }
-}
-
-impl convert::AsRef<[u8]> for Buffer {
-    fn as_ref(&self) -> &[u8] {
-        &self.bytes
-    }
-}
+    let mut plaintext = padding_oracle::padding_oracle_decrypt(&service, &iv, &ciphertext);
+    plaintext.unpad();
 
-impl fmt::Display for Buffer {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", String::from_utf8_lossy(&self.bytes))
-    }
-}
-
-fn best_one_byte_xor(buffer: &Buffer) -> (u8, f64) {
-    let mut best_key = 0u8;
-    let mut best_penalty = score::english_text_frequency(buffer.xor([0]).as_ref());
-
-    for k in 1..u8::MAX {
-        let text = buffer.xor([k]);
-        let penalty = score::english_text_frequency(text.as_ref());
-
-        if penalty < best_penalty {
-            best_penalty = penalty;
-            best_key = k;
-        }
-    }
-
-    (best_key, best_penalty)
-}
-
-fn hamming_distance(one: &[u8], two: &[u8]) -> usize {
-    one.iter()
-        .zip(two.iter())
-        .map(|(a, b)| (a ^ b).count_ones() as usize)
-        .sum()
-}
-
-fn encryption_oracle(plaintext: &[u8]) -> Buffer {
-    lazy_static! {
-        static ref SECRET: Buffer = Buffer::from_base64(
-            "Um9sbGluJyBpbiBteSA1LjAKV2l0aCBteSByYWctdG9wIGRvd24gc28gbXkg\
-            aGFpciBjYW4gYmxvdwpUaGUgZ2lybGllcyBvbiBzdGFuZGJ5IHdhdmluZyBq\
-            dXN0IHRvIHNheSBoaQpEaWQgeW91IHN0b3A/IE5vLCBJIGp1c3QgZHJvdmUg\
-            YnkK",
-        );
-    }
-
-    lazy_static! {
-        static ref KEY: Vec<u8> = linux_random::random(16);
-    }
-
-    Buffer {
-        bytes: openssl::symm::encrypt(
-            openssl::symm::Cipher::aes_128_ecb(),
-            &KEY,
-            None,
-            &plaintext
-                .iter()
-                .copied()
-                .chain(SECRET.bytes.iter().copied())
-                .collect::<Vec<u8>>(),
-        )
-        .unwrap(),
-    }
-}
-
-fn main() {
-    let k = KeyValue::profile_for("foo@bar.com");
-    println!("{:?}", k);
-    println!("{}", k.encode());
+    println!("{}", plaintext);
 }
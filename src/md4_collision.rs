@@ -0,0 +1,386 @@
+use crate::hash::md4::{self, INITIAL_STATE, ROUND_2, ROUND_3};
+use crate::random::RandomSource;
+
+/// A sufficient condition on one bit of a chaining value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Condition {
+    Zero,
+    One,
+    /// Equal to the same bit of the value computed `n` steps earlier.
+    Equal(usize),
+    /// The opposite of the same bit of the value computed `n` steps earlier.
+    Differ(usize),
+}
+
+use Condition::*;
+
+/// The conditions of Wang et al. on `a1, d1, c1, b1, a2, ...`, one entry per
+/// step with bits numbered from 1 as in the paper.
+const CONDITIONS: [&[(u32, Condition)]; 37] = [
+    // a1 to b1.
+    &[(7, Equal(1))],
+    &[(7, Zero), (8, Equal(1)), (11, Equal(1))],
+    &[(7, One), (8, One), (11, Zero), (26, Equal(1))],
+    &[(7, One), (8, Zero), (11, Zero), (26, Zero)],
+    // a2 to b2.
+    &[(8, One), (11, One), (26, Zero), (14, Equal(1))],
+    &[
+        (14, Zero),
+        (19, Equal(1)),
+        (20, Equal(1)),
+        (21, Equal(1)),
+        (22, Equal(1)),
+        (26, One),
+    ],
+    &[
+        (13, Equal(1)),
+        (14, Zero),
+        (15, Equal(1)),
+        (19, Zero),
+        (20, Zero),
+        (21, One),
+        (22, Zero),
+    ],
+    &[
+        (13, One),
+        (14, One),
+        (15, Zero),
+        (17, Equal(1)),
+        (19, Zero),
+        (20, Zero),
+        (21, Zero),
+        (22, Zero),
+    ],
+    // a3 to b3.
+    &[
+        (13, One),
+        (14, One),
+        (15, One),
+        (17, Zero),
+        (19, Zero),
+        (20, Zero),
+        (21, Zero),
+        (22, One),
+        (23, Equal(1)),
+        (26, Equal(1)),
+    ],
+    &[
+        (13, One),
+        (14, One),
+        (15, One),
+        (17, Zero),
+        (20, Zero),
+        (21, One),
+        (22, One),
+        (23, Zero),
+        (26, One),
+        (30, Equal(1)),
+    ],
+    &[
+        (17, One),
+        (20, Zero),
+        (21, Zero),
+        (22, Zero),
+        (23, Zero),
+        (26, Zero),
+        (30, One),
+        (32, Equal(1)),
+    ],
+    &[
+        (20, Zero),
+        (21, One),
+        (22, One),
+        (23, Equal(1)),
+        (26, One),
+        (30, Zero),
+        (32, Zero),
+    ],
+    // a4 to b4.
+    &[
+        (23, Zero),
+        (26, Zero),
+        (27, Equal(1)),
+        (29, Equal(1)),
+        (30, One),
+        (32, Zero),
+    ],
+    &[
+        (23, Zero),
+        (26, Zero),
+        (27, One),
+        (29, One),
+        (30, Zero),
+        (32, One),
+    ],
+    &[
+        (19, Equal(1)),
+        (23, One),
+        (26, One),
+        (27, Zero),
+        (29, Zero),
+        (30, Zero),
+    ],
+    &[(19, Zero), (26, Equal(1)), (27, One), (29, One), (30, Zero)],
+    // a5 to b5.
+    &[(19, Equal(2)), (26, One), (27, Zero), (29, One), (32, One)],
+    &[
+        (19, Equal(1)),
+        (26, Equal(2)),
+        (27, Equal(2)),
+        (29, Equal(2)),
+        (32, Equal(2)),
+    ],
+    &[
+        (26, Equal(1)),
+        (27, Equal(1)),
+        (29, Equal(1)),
+        (30, Equal(1)),
+        (32, Equal(1)),
+    ],
+    &[(29, Equal(1)), (30, One), (32, Zero)],
+    // a6 to c6.
+    &[(29, One), (32, One)],
+    &[(29, Equal(2))],
+    &[(29, Equal(1)), (30, Differ(1)), (32, Differ(1))],
+    // Nothing more from b6 to c9.
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    &[],
+    // b9 and a10.
+    &[(32, One)],
+    &[(32, One)],
+];
+
+const ROUND_1_SHIFTS: [u32; 4] = [3, 7, 11, 19];
+const ROUND_2_SHIFTS: [u32; 4] = [3, 5, 9, 13];
+const ROUND_3_SHIFTS: [u32; 4] = [3, 9, 11, 15];
+
+const ROUND_2_ORDER: [usize; 16] = [0, 4, 8, 12, 1, 5, 9, 13, 2, 6, 10, 14, 3, 7, 11, 15];
+const ROUND_3_ORDER: [usize; 16] = [0, 8, 4, 12, 2, 10, 6, 14, 1, 9, 5, 13, 3, 11, 7, 15];
+
+/// The chaining values of one compression: the initial `a, d, c, b`
+/// followed by the value computed at each of the 48 steps.
+type Chain = [u32; 52];
+
+fn step(chain: &Chain, m: &[u32; 16], step: usize) -> u32 {
+    let [x, w, z, y] = [
+        chain[step],
+        chain[step + 1],
+        chain[step + 2],
+        chain[step + 3],
+    ];
+
+    let (mixed, word, shift) = match step / 16 {
+        0 => (md4::f(y, z, w), m[step], ROUND_1_SHIFTS[step % 4]),
+        1 => (
+            md4::g(y, z, w).wrapping_add(ROUND_2),
+            m[ROUND_2_ORDER[step % 16]],
+            ROUND_2_SHIFTS[step % 4],
+        ),
+        _ => (
+            md4::h(y, z, w).wrapping_add(ROUND_3),
+            m[ROUND_3_ORDER[step % 16]],
+            ROUND_3_SHIFTS[step % 4],
+        ),
+    };
+
+    x.wrapping_add(mixed).wrapping_add(word).rotate_left(shift)
+}
+
+/// The first-round message word that makes step `step` produce the value
+/// already in the chain.
+fn message_word(chain: &Chain, step: usize) -> u32 {
+    let [x, w, z, y] = [
+        chain[step],
+        chain[step + 1],
+        chain[step + 2],
+        chain[step + 3],
+    ];
+
+    chain[step + 4]
+        .rotate_right(ROUND_1_SHIFTS[step % 4])
+        .wrapping_sub(x)
+        .wrapping_sub(md4::f(y, z, w))
+}
+
+fn chain(m: &[u32; 16]) -> Chain {
+    let mut chain = [0; 52];
+    chain[..4].copy_from_slice(&[
+        INITIAL_STATE[0],
+        INITIAL_STATE[3],
+        INITIAL_STATE[2],
+        INITIAL_STATE[1],
+    ]);
+
+    for i in 0..48 {
+        chain[i + 4] = step(&chain, m, i);
+    }
+
+    chain
+}
+
+/// The bits of the value computed at `step` that violate its conditions.
+fn violations(chain: &Chain, step: usize, value: u32) -> u32 {
+    CONDITIONS[step]
+        .iter()
+        .filter(|(bit, condition)| {
+            let bit = bit - 1;
+            let actual = value >> bit & 1;
+
+            let expected = match condition {
+                Zero => 0,
+                One => 1,
+                Equal(back) => chain[step + 4 - back] >> bit & 1,
+                Differ(back) => !chain[step + 4 - back] >> bit & 1,
+            };
+
+            actual != expected
+        })
+        .fold(0, |mask, (bit, _)| mask | 1 << (bit - 1))
+}
+
+/// Make every first-round condition hold by fixing the bits of each value
+/// and solving for the message word producing it.
+fn single_step_modification(m: &mut [u32; 16]) -> Chain {
+    let mut chain = chain(m);
+
+    for i in 0..16 {
+        let value = step(&chain, m, i);
+        chain[i + 4] = value ^ violations(&chain, i, value);
+        m[i] = message_word(&chain, i);
+    }
+
+    chain
+}
+
+/// Flip `bits` of the value of first-round step `target`, then fix the
+/// message words from there on so that the following four values, and with
+/// them the rest of the first round, stay the same.
+fn flip_first_round(chain: &mut Chain, m: &mut [u32; 16], target: usize, bits: u32) {
+    chain[target + 4] ^= bits;
+
+    for (i, word) in m.iter_mut().enumerate().skip(target).take(5) {
+        *word = message_word(chain, i);
+    }
+}
+
+/// Correct the conditions on `a5` and `d5` through the first-round values
+/// sharing their message words, `a1` and `a2`.
+fn multi_step_modification(chain: &mut Chain, m: &mut [u32; 16]) {
+    for (target, corrected, shift) in [(0, 16, 0), (4, 17, 2)] {
+        for bit in 0..32 {
+            let value = step(chain, m, corrected);
+
+            if violations(chain, corrected, value) >> bit & 1 == 1 {
+                flip_first_round(chain, m, target, 1 << ((bit + 32 - shift) % 32));
+            }
+        }
+
+        chain[corrected + 4] = step(chain, m, corrected);
+    }
+}
+
+/// The partner of `m` under Wang's differential.
+pub fn differential(m: &[u32; 16]) -> [u32; 16] {
+    let mut partner = *m;
+
+    partner[1] = partner[1].wrapping_add(1 << 31);
+    partner[2] = partner[2].wrapping_add((1 << 31) - (1 << 28));
+    partner[12] = partner[12].wrapping_sub(1 << 16);
+
+    partner
+}
+
+/// Find two different one-block messages with the same MD4 compression
+/// output from the standard initial state, and so the same MD4 digest.
+///
+/// Random messages are massaged so that the conditions of the first round
+/// and on `a5` and `d5` always hold; the few conditions left hold by chance
+/// every hundred thousand or so tries, after which the differential
+/// collides with high probability.
+pub fn find_collision(random: &mut dyn RandomSource) -> ([u8; 64], [u8; 64]) {
+    let mut block = [0; 64];
+
+    loop {
+        random.fill(&mut block);
+
+        let mut m = [0; 16];
+
+        for (word, chunk) in m.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_le_bytes(chunk.try_into().unwrap());
+        }
+
+        let mut chain = single_step_modification(&mut m);
+        multi_step_modification(&mut chain, &mut m);
+
+        let chain = self::chain(&m);
+
+        if (0..CONDITIONS.len()).any(|i| violations(&chain, i, chain[i + 4]) != 0) {
+            continue;
+        }
+
+        let partner = differential(&m);
+        let (mut one, mut two) = (INITIAL_STATE, INITIAL_STATE);
+
+        md4::compress(&mut one, &m);
+        md4::compress(&mut two, &partner);
+
+        if one == two {
+            return (to_bytes(&m), to_bytes(&partner));
+        }
+    }
+}
+
+fn to_bytes(m: &[u32; 16]) -> [u8; 64] {
+    let mut block = [0; 64];
+
+    for (chunk, word) in block.chunks_exact_mut(4).zip(m) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::{md4::Md4, Hash};
+    use crate::random::{ChaCha20Rng, TEST_SEED};
+
+    #[test]
+    fn first_round_conditions_are_forced() {
+        let mut random = ChaCha20Rng::seed_from_u64(TEST_SEED);
+
+        for _ in 0..100 {
+            let mut m = [0; 16];
+
+            for word in m.iter_mut() {
+                *word = random.next_u64() as u32;
+            }
+
+            let mut chain = single_step_modification(&mut m);
+            multi_step_modification(&mut chain, &mut m);
+
+            let chain = self::chain(&m);
+
+            assert!((0..16).all(|i| violations(&chain, i, chain[i + 4]) == 0));
+        }
+    }
+
+    #[test]
+    fn wang_attack_finds_md4_collision() {
+        let (one, two) = find_collision(&mut ChaCha20Rng::seed_from_u64(TEST_SEED));
+
+        assert_ne!(one, two);
+        assert_eq!(Md4::digest(&one), Md4::digest(&two));
+    }
+}
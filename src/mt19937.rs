@@ -0,0 +1,382 @@
+use crate::clock::Clock;
+use crate::random::RandomSource;
+use std::iter;
+use std::ops::RangeInclusive;
+
+pub const N: usize = 624;
+pub const M: usize = 397;
+pub const MATRIX_A: u32 = 0x9908_b0df;
+const UPPER_MASK: u32 = 0x8000_0000;
+const LOWER_MASK: u32 = 0x7fff_ffff;
+
+/// The 32-bit Mersenne Twister, matching the reference `mt19937ar.c`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mt19937 {
+    state: [u32; N],
+    index: usize,
+}
+
+impl Mt19937 {
+    /// Seed like the reference `init_genrand`.
+    pub fn new(seed: u32) -> Self {
+        let mut state = [0; N];
+        state[0] = seed;
+
+        for i in 1..N {
+            state[i] = 1_812_433_253u32
+                .wrapping_mul(state[i - 1] ^ (state[i - 1] >> 30))
+                .wrapping_add(i as u32);
+        }
+
+        Self { state, index: N }
+    }
+
+    /// Seed like the reference `init_by_array`.
+    pub fn from_array(key: &[u32]) -> Self {
+        let mut mt = Self::new(19_650_218);
+        let state = &mut mt.state;
+
+        let (mut i, mut j) = (1, 0);
+
+        for _ in 0..std::cmp::max(N, key.len()) {
+            state[i] = (state[i] ^ (state[i - 1] ^ (state[i - 1] >> 30)).wrapping_mul(1_664_525))
+                .wrapping_add(key.get(j).copied().unwrap_or_default())
+                .wrapping_add(j as u32);
+
+            i += 1;
+            j += 1;
+
+            if i >= N {
+                state[0] = state[N - 1];
+                i = 1;
+            }
+
+            if j >= key.len() {
+                j = 0;
+            }
+        }
+
+        for _ in 0..N - 1 {
+            state[i] = (state[i]
+                ^ (state[i - 1] ^ (state[i - 1] >> 30)).wrapping_mul(1_566_083_941))
+            .wrapping_sub(i as u32);
+
+            i += 1;
+
+            if i >= N {
+                state[0] = state[N - 1];
+                i = 1;
+            }
+        }
+
+        state[0] = UPPER_MASK;
+
+        mt
+    }
+
+    /// Resume from a raw state, where `index` is the next word to output
+    /// and `N` means a twist is due.
+    pub fn from_state(state: [u32; N], index: usize) -> Self {
+        assert!(index <= N, "state index out of range");
+
+        Self { state, index }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        if self.index >= N {
+            self.twist();
+        }
+
+        let y = self.state[self.index];
+        self.index += 1;
+
+        temper(y)
+    }
+
+    fn twist(&mut self) {
+        for i in 0..N {
+            let y = (self.state[i] & UPPER_MASK) | (self.state[(i + 1) % N] & LOWER_MASK);
+            let mag = if y & 1 == 1 { MATRIX_A } else { 0 };
+
+            self.state[i] = self.state[(i + M) % N] ^ (y >> 1) ^ mag;
+        }
+
+        self.index = 0;
+    }
+}
+
+impl iter::Iterator for Mt19937 {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_u32())
+    }
+}
+
+pub fn temper(mut y: u32) -> u32 {
+    y ^= y >> 11;
+    y ^= (y << 7) & 0x9d2c_5680;
+    y ^= (y << 15) & 0xefc6_0000;
+    y ^ (y >> 18)
+}
+
+pub fn untemper(mut y: u32) -> u32 {
+    y = undo_right_shift_xor(y, 18, u32::MAX);
+    y = undo_left_shift_xor(y, 15, 0xefc6_0000);
+    y = undo_left_shift_xor(y, 7, 0x9d2c_5680);
+    undo_right_shift_xor(y, 11, u32::MAX)
+}
+
+/// Invert `y ^= (y >> shift) & mask`; every pass fixes `shift` more bits.
+fn undo_right_shift_xor(y: u32, shift: u32, mask: u32) -> u32 {
+    (0..32 / shift).fold(y, |x, _| y ^ ((x >> shift) & mask))
+}
+
+/// Invert `y ^= (y << shift) & mask`; every pass fixes `shift` more bits.
+fn undo_left_shift_xor(y: u32, shift: u32, mask: u32) -> u32 {
+    (0..32 / shift).fold(y, |x, _| y ^ ((x << shift) & mask))
+}
+
+/// Clone a generator from 624 consecutive outputs, after which it predicts
+/// every output that follows them.
+pub fn clone_from_outputs(outputs: &[u32; N]) -> Mt19937 {
+    let mut state = [0; N];
+
+    for (word, output) in state.iter_mut().zip(outputs.iter()) {
+        *word = untemper(*output);
+    }
+
+    Mt19937 { state, index: N }
+}
+
+/// Clone a generator from at least 624 consecutive outputs taken anywhere in
+/// its stream, returning it positioned after the last of them.
+///
+/// The twist recurrence holds between any 624 consecutive untempered
+/// outputs, not just those from one twist, so no alignment is needed; the
+/// outputs past the first 624 are used to check that the clone is right.
+pub fn clone_from_stream(outputs: &[u32]) -> Option<Mt19937> {
+    let (head, tail) = outputs.split_at_checked(N)?;
+    let mut mt = clone_from_outputs(head.try_into().ok()?);
+
+    if tail.iter().all(|output| mt.next_u32() == *output) {
+        Some(mt)
+    } else {
+        None
+    }
+}
+
+/// Find the seed in `window` whose generator starts with `output`, trying the
+/// most recent timestamps first.
+pub fn crack_time_seed(output: u32, window: RangeInclusive<u32>) -> Option<u32> {
+    window
+        .rev()
+        .find(|seed| Mt19937::new(*seed).next_u32() == output)
+}
+
+/// Wait a random 40 to 1000 seconds, seed with the current time, wait again
+/// and return the first output.
+pub fn time_seeded_output<C: Clock>(clock: &C, random: &mut dyn RandomSource) -> u32 {
+    clock.sleep(40 + random.below(961));
+    let mut mt = Mt19937::new(clock.now() as u32);
+    clock.sleep(40 + random.below(961));
+
+    mt.next_u32()
+}
+
+const N_64: usize = 312;
+const M_64: usize = 156;
+const MATRIX_A_64: u64 = 0xb502_6f5a_a966_19e9;
+const UPPER_MASK_64: u64 = 0xffff_ffff_8000_0000;
+const LOWER_MASK_64: u64 = 0x7fff_ffff;
+
+/// The 64-bit Mersenne Twister, matching the reference `mt19937-64.c`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mt19937_64 {
+    state: [u64; N_64],
+    index: usize,
+}
+
+impl Mt19937_64 {
+    /// Seed like the reference `init_genrand64`.
+    pub fn new(seed: u64) -> Self {
+        let mut state = [0; N_64];
+        state[0] = seed;
+
+        for i in 1..N_64 {
+            state[i] = 6_364_136_223_846_793_005u64
+                .wrapping_mul(state[i - 1] ^ (state[i - 1] >> 62))
+                .wrapping_add(i as u64);
+        }
+
+        Self { state, index: N_64 }
+    }
+
+    /// Seed like the reference `init_by_array64`.
+    pub fn from_array(key: &[u64]) -> Self {
+        let mut mt = Self::new(19_650_218);
+        let state = &mut mt.state;
+
+        let (mut i, mut j) = (1, 0);
+
+        for _ in 0..std::cmp::max(N_64, key.len()) {
+            state[i] = (state[i]
+                ^ (state[i - 1] ^ (state[i - 1] >> 62)).wrapping_mul(3_935_559_000_370_003_845))
+            .wrapping_add(key.get(j).copied().unwrap_or_default())
+            .wrapping_add(j as u64);
+
+            i += 1;
+            j += 1;
+
+            if i >= N_64 {
+                state[0] = state[N_64 - 1];
+                i = 1;
+            }
+
+            if j >= key.len() {
+                j = 0;
+            }
+        }
+
+        for _ in 0..N_64 - 1 {
+            state[i] = (state[i]
+                ^ (state[i - 1] ^ (state[i - 1] >> 62)).wrapping_mul(2_862_933_555_777_941_757))
+            .wrapping_sub(i as u64);
+
+            i += 1;
+
+            if i >= N_64 {
+                state[0] = state[N_64 - 1];
+                i = 1;
+            }
+        }
+
+        state[0] = 1 << 63;
+
+        mt
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        if self.index >= N_64 {
+            self.twist();
+        }
+
+        let mut x = self.state[self.index];
+        self.index += 1;
+
+        x ^= (x >> 29) & 0x5555_5555_5555_5555;
+        x ^= (x << 17) & 0x71d6_7fff_eda6_0000;
+        x ^= (x << 37) & 0xfff7_eee0_0000_0000;
+        x ^ (x >> 43)
+    }
+
+    fn twist(&mut self) {
+        for i in 0..N_64 {
+            let x = (self.state[i] & UPPER_MASK_64) | (self.state[(i + 1) % N_64] & LOWER_MASK_64);
+            let mag = if x & 1 == 1 { MATRIX_A_64 } else { 0 };
+
+            self.state[i] = self.state[(i + M_64) % N_64] ^ (x >> 1) ^ mag;
+        }
+
+        self.index = 0;
+    }
+}
+
+impl iter::Iterator for Mt19937_64 {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_u64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SimulatedClock;
+    use crate::random::{ChaCha20Rng, TEST_SEED};
+
+    #[test]
+    fn mt19937_matches_reference_array_seeding() {
+        let outputs: Vec<u32> = Mt19937::from_array(&[0x123, 0x234, 0x345, 0x456])
+            .take(5)
+            .collect();
+
+        assert_eq!(
+            outputs,
+            [1067595299, 955945823, 477289528, 4107218783, 4228976476]
+        );
+    }
+
+    #[test]
+    fn mt19937_matches_default_seed() {
+        let mut mt = Mt19937::new(5489);
+
+        assert_eq!(mt.next_u32(), 3499211612);
+        assert_eq!(mt.nth(9998), Some(4123659995));
+    }
+
+    #[test]
+    fn time_seed_is_recovered() {
+        let clock = SimulatedClock::new(1_700_000_000);
+        let start = clock.now() as u32;
+
+        let output = time_seeded_output(&clock, &mut ChaCha20Rng::seed_from_u64(TEST_SEED));
+        let seed = crack_time_seed(output, start..=clock.now() as u32).unwrap();
+
+        assert!(seed >= start + 40);
+        assert_eq!(Mt19937::new(seed).next_u32(), output);
+    }
+
+    #[test]
+    fn untemper_inverts_temper() {
+        for y in [0, 1, 0x8000_0000, 0xdead_beef, u32::MAX] {
+            assert_eq!(untemper(temper(y)), y);
+        }
+    }
+
+    #[test]
+    fn clone_predicts_future_outputs() {
+        let mut mt = Mt19937::new(0x5eed);
+        let outputs: [u32; N] = (&mut mt).take(N).collect::<Vec<u32>>().try_into().unwrap();
+
+        let clone = clone_from_outputs(&outputs);
+
+        assert!(mt.take(2000).eq(clone.take(2000)));
+    }
+
+    #[test]
+    fn clone_from_unaligned_stream() {
+        let mut mt = Mt19937::new(0xfeed);
+        mt.nth(300);
+
+        let outputs = (&mut mt).take(N + 50).collect::<Vec<u32>>();
+        let clone = clone_from_stream(&outputs).unwrap();
+
+        assert!(mt.take(2000).eq(clone.take(2000)));
+        assert!(clone_from_stream(&outputs[..N - 1]).is_none());
+    }
+
+    #[test]
+    fn mt19937_64_matches_reference_array_seeding() {
+        let outputs: Vec<u64> = Mt19937_64::from_array(&[0x12345, 0x23456, 0x34567, 0x45678])
+            .take(5)
+            .collect();
+
+        assert_eq!(
+            outputs,
+            [
+                7266447313870364031,
+                4946485549665804864,
+                16945909448695747420,
+                16394063075524226720,
+                4873882236456199058
+            ]
+        );
+    }
+
+    #[test]
+    fn mt19937_64_matches_default_seed() {
+        assert_eq!(Mt19937_64::new(5489).nth(9999), Some(9981545732273789042));
+    }
+}
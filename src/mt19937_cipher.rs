@@ -0,0 +1,170 @@
+use crate::clock::Clock;
+use crate::crypto::Keystream;
+use crate::mt19937::Mt19937;
+use crate::random::RandomSource;
+use crate::Buffer;
+use std::iter;
+
+/// A stream cipher whose keystream is the little-endian bytes of an MT19937
+/// seeded with a 16-bit key.
+pub struct Mt19937Cipher {
+    seed: u32,
+    mt: Mt19937,
+    position: u64,
+    output: [u8; 4],
+}
+
+impl Mt19937Cipher {
+    pub fn new(key: u16) -> Self {
+        Self::with_seed(key as u32)
+    }
+
+    fn with_seed(seed: u32) -> Self {
+        Self {
+            seed,
+            mt: Mt19937::new(seed),
+            position: 0,
+            output: [0; 4],
+        }
+    }
+}
+
+impl iter::Iterator for Mt19937Cipher {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position.is_multiple_of(4) {
+            self.output = self.mt.next_u32().to_le_bytes();
+        }
+
+        self.position += 1;
+
+        Some(self.output[(self.position - 1) as usize % 4])
+    }
+}
+
+impl Keystream for Mt19937Cipher {
+    fn seek(&mut self, offset: u64) {
+        let mut mt = Mt19937::new(self.seed);
+
+        for _ in 0..offset / 4 {
+            mt.next_u32();
+        }
+
+        self.mt = mt;
+        self.position = offset - offset % 4;
+
+        for _ in 0..offset % 4 {
+            self.next();
+        }
+    }
+}
+
+/// Encrypt `known` behind a random number of random bytes.
+pub fn encrypt_with_random_prefix(key: u16, known: &[u8], random: &mut dyn RandomSource) -> Buffer {
+    let prefix_length = 5 + random.below(20) as usize;
+    let plaintext = random
+        .bytes(prefix_length)
+        .into_iter()
+        .chain(known.iter().copied())
+        .collect::<Vec<u8>>();
+
+    Buffer::new(&Mt19937Cipher::new(key).apply(&plaintext))
+}
+
+/// Find the 16-bit key of a ciphertext whose plaintext ends in `known`, or
+/// `None` if nothing is known.
+pub fn recover_key(ciphertext: &Buffer, known: &[u8]) -> Option<u16> {
+    if known.is_empty() {
+        return None;
+    }
+
+    let offset = ciphertext.len().checked_sub(known.len())?;
+    let expected = Buffer::new(&ciphertext.as_ref()[offset..]).xor(known);
+
+    (0..=u16::MAX).find(|key| {
+        let mut cipher = Mt19937Cipher::new(*key);
+        cipher.seek(offset as u64);
+
+        cipher
+            .take(known.len())
+            .eq(expected.as_ref().iter().copied())
+    })
+}
+
+const TOKEN_LENGTH: usize = 16;
+
+/// A password reset token made from an MT19937 seeded with the current time.
+pub fn reset_token<C: Clock>(clock: &C) -> String {
+    let token = Mt19937Cipher::with_seed(clock.now() as u32)
+        .take(TOKEN_LENGTH)
+        .collect::<Vec<u8>>();
+
+    hex::encode(token)
+}
+
+/// Check whether `token` came from an MT19937 seeded with a time within
+/// `window` seconds before now. An empty token matches any seed and is
+/// rejected.
+pub fn is_time_seeded_token<C: Clock>(token: &str, clock: &C, window: u64) -> bool {
+    let token = match hex::decode(token) {
+        Ok(token) if !token.is_empty() => token,
+        _ => return false,
+    };
+
+    let now = clock.now();
+
+    (now.saturating_sub(window)..=now).any(|time| {
+        Mt19937Cipher::with_seed(time as u32)
+            .take(token.len())
+            .eq(token.iter().copied())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SimulatedClock;
+    use crate::random::{ChaCha20Rng, TEST_SEED};
+
+    const KNOWN: &[u8] = b"AAAAAAAAAAAAAA";
+
+    #[test]
+    fn cipher_round_trips_and_seeks() {
+        let plaintext = b"Mersenne Twister as a stream cipher";
+        let ciphertext = Mt19937Cipher::new(0x1234).apply(plaintext);
+
+        assert_eq!(Mt19937Cipher::new(0x1234).apply(&ciphertext), plaintext);
+
+        let mut cipher = Mt19937Cipher::new(0x1234);
+        cipher.seek(11);
+
+        assert_eq!(cipher.apply(&ciphertext[11..]), &plaintext[11..]);
+    }
+
+    #[test]
+    fn key_is_recovered_from_known_suffix() {
+        let mut random = ChaCha20Rng::seed_from_u64(TEST_SEED);
+        let key = random.below(1 << 16) as u16;
+        let ciphertext = encrypt_with_random_prefix(key, KNOWN, &mut random);
+
+        assert_eq!(recover_key(&ciphertext, KNOWN), Some(key));
+        assert_eq!(recover_key(&ciphertext, b""), None);
+    }
+
+    #[test]
+    fn time_seeded_tokens_are_detected() {
+        let clock = SimulatedClock::new(1_700_000_000);
+        let token = reset_token(&clock);
+
+        clock.sleep(90);
+
+        assert!(is_time_seeded_token(&token, &clock, 3600));
+        assert!(!is_time_seeded_token("", &clock, 3600));
+        assert!(!is_time_seeded_token(
+            &hex::encode(ChaCha20Rng::seed_from_u64(TEST_SEED).bytes(TOKEN_LENGTH)),
+            &clock,
+            3600
+        ));
+    }
+}
@@ -0,0 +1,272 @@
+use crate::gf2::{BitVector, LinearSystem};
+use crate::mt19937::{Mt19937, M, MATRIX_A, N};
+
+const UNKNOWNS: usize = N * 32;
+
+/// The lower 31 bits of the first word never reach an output, so this is the
+/// most the equations can pin down.
+const FULL_RANK: usize = UNKNOWNS - 31;
+
+/// The bits of one output that were observed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Leak {
+    mask: u32,
+    value: u32,
+}
+
+impl Leak {
+    pub fn new(mask: u32, value: u32) -> Self {
+        Self {
+            mask,
+            value: value & mask,
+        }
+    }
+
+    pub fn full(output: u32) -> Self {
+        Self::new(u32::MAX, output)
+    }
+
+    /// The top `bits` bits, given as `output >> (32 - bits)`.
+    pub fn top_bits(bits: u32, leaked: u32) -> Self {
+        assert!((1..=32).contains(&bits), "cannot leak {} bits", bits);
+
+        Self::new(u32::MAX << (32 - bits), leaked << (32 - bits))
+    }
+
+    /// The bottom `bits` bits, given as `output % 2^bits`; this is what
+    /// `rand() % n` leaks when `n` is a power of two.
+    pub fn low_bits(bits: u32, leaked: u32) -> Self {
+        assert!((1..=32).contains(&bits), "cannot leak {} bits", bits);
+
+        Self::new(u32::MAX >> (32 - bits), leaked)
+    }
+
+    pub fn matches(&self, output: u32) -> bool {
+        output & self.mask == self.value
+    }
+}
+
+/// One 32-bit word as linear functions of the unknown state, bit by bit.
+type Word = Vec<BitVector>;
+
+/// MT19937 run over GF(2): every state bit is tracked as a linear
+/// combination of the bits of the state before the first twist.
+struct SymbolicMt {
+    state: Vec<Word>,
+    index: usize,
+}
+
+impl SymbolicMt {
+    fn new() -> Self {
+        Self {
+            state: (0..N)
+                .map(|word| {
+                    (0..32)
+                        .map(|bit| BitVector::unit(UNKNOWNS, 32 * word + bit))
+                        .collect()
+                })
+                .collect(),
+            index: N,
+        }
+    }
+
+    fn next_word(&mut self) -> Word {
+        if self.index >= N {
+            self.twist();
+        }
+
+        let y = &self.state[self.index];
+        self.index += 1;
+
+        let y = shift_xor(y, -11, u32::MAX);
+        let y = shift_xor(&y, 7, 0x9d2c_5680);
+        let y = shift_xor(&y, 15, 0xefc6_0000);
+        shift_xor(&y, -18, u32::MAX)
+    }
+
+    fn twist(&mut self) {
+        for i in 0..N {
+            // `y` takes its top bit from word `i` and the rest from the next.
+            let y = |bit: usize| {
+                if bit == 31 {
+                    &self.state[i][31]
+                } else {
+                    &self.state[(i + 1) % N][bit]
+                }
+            };
+
+            let word = (0..32)
+                .map(|bit| {
+                    let mut combination = self.state[(i + M) % N][bit].clone();
+
+                    if bit < 31 {
+                        combination ^= y(bit + 1);
+                    }
+
+                    if MATRIX_A >> bit & 1 == 1 {
+                        combination ^= y(0);
+                    }
+
+                    combination
+                })
+                .collect();
+
+            self.state[i] = word;
+        }
+
+        self.index = 0;
+    }
+}
+
+/// `y ^ ((y << shift) & mask)`, shifting right for negative `shift`.
+fn shift_xor(y: &[BitVector], shift: i32, mask: u32) -> Word {
+    (0..32)
+        .map(|bit| {
+            let mut combination = y[bit].clone();
+            let source = bit as i32 - shift;
+
+            if mask >> bit & 1 == 1 && (0..32).contains(&source) {
+                combination ^= &y[source as usize];
+            }
+
+            combination
+        })
+        .collect()
+}
+
+/// Recover a generator from consecutive outputs of which only some bits were
+/// observed, `None` marking outputs that leaked nothing.
+///
+/// Every observed bit is a linear equation in the state before the first
+/// twist; once the equations have full rank the state is solved for by
+/// Gaussian elimination. The returned generator is positioned after the
+/// last output and agrees with every leak, or `None` is returned.
+pub fn recover_from_leaks(leaks: &[Option<Leak>]) -> Option<Mt19937> {
+    let mut model = SymbolicMt::new();
+    let mut system = LinearSystem::new(UNKNOWNS);
+
+    for leak in leaks {
+        if system.rank() == FULL_RANK {
+            break;
+        }
+
+        let word = model.next_word();
+
+        if let Some(leak) = leak {
+            for (bit, combination) in word.into_iter().enumerate() {
+                if leak.mask >> bit & 1 == 1 {
+                    system.add(combination, leak.value >> bit & 1 == 1);
+                }
+            }
+        }
+    }
+
+    if system.rank() < FULL_RANK {
+        return None;
+    }
+
+    let solution = system.solve()?;
+    let mut state = [0; N];
+
+    for (i, word) in state.iter_mut().enumerate() {
+        *word = (0..32).fold(0, |word, bit| {
+            word | (solution.get(32 * i + bit) as u32) << bit
+        });
+    }
+
+    let mut mt = Mt19937::from_state(state, N);
+
+    leaks
+        .iter()
+        .all(|leak| {
+            let output = mt.next_u32();
+            leak.is_none_or(|leak| leak.matches(output))
+        })
+        .then_some(mt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mt19937;
+
+    /// Check the symbolic model against the real generator: evaluating the
+    /// tracked combinations at a concrete state must give its outputs.
+    fn model_matches(state: &[u32; N], outputs: usize) -> bool {
+        let mut bits = BitVector::zeros(UNKNOWNS);
+
+        for (i, word) in state.iter().enumerate() {
+            for bit in 0..32 {
+                bits.set(32 * i + bit, word >> bit & 1 == 1);
+            }
+        }
+
+        let mut model = SymbolicMt::new();
+        let mut mt = Mt19937::from_state(*state, N);
+
+        (0..outputs).all(|_| {
+            let word = model.next_word();
+            let expected = mt.next_u32();
+
+            (0..32).all(|bit| word[bit].dot(&bits) == (expected >> bit & 1 == 1))
+        })
+    }
+
+    fn outputs(seed: u32, count: usize) -> (Mt19937, Vec<u32>) {
+        let mut mt = Mt19937::new(seed);
+        mt.nth(100);
+
+        let outputs = (&mut mt).take(count).collect();
+
+        (mt, outputs)
+    }
+
+    #[test]
+    fn symbolic_model_tracks_real_generator() {
+        let mut state = [0; N];
+
+        for (word, output) in state.iter_mut().zip(Mt19937::new(7)) {
+            *word = mt19937::untemper(output);
+        }
+
+        assert!(model_matches(&state, N + 50));
+    }
+
+    #[test]
+    fn too_few_leaks_give_nothing() {
+        let (_, outputs) = outputs(1, 100);
+        let leaks = outputs
+            .iter()
+            .map(|output| Some(Leak::full(*output)))
+            .collect::<Vec<_>>();
+
+        assert!(recover_from_leaks(&leaks).is_none());
+    }
+
+    #[test]
+    fn recovers_state_from_top_bytes() {
+        let (mt, outputs) = outputs(0xc0ffee, 2600);
+        let leaks = outputs
+            .iter()
+            .map(|output| Some(Leak::top_bits(8, output >> 24)))
+            .collect::<Vec<_>>();
+
+        let clone = recover_from_leaks(&leaks).unwrap();
+
+        assert!(mt.take(1000).eq(clone.take(1000)));
+    }
+
+    #[test]
+    fn recovers_state_from_low_bits_with_gaps() {
+        let (mt, outputs) = outputs(0xbeef, 12000);
+        let leaks = outputs
+            .iter()
+            .enumerate()
+            .map(|(i, output)| (i % 3 != 0).then_some(Leak::low_bits(5, output % 32)))
+            .collect::<Vec<_>>();
+
+        let clone = recover_from_leaks(&leaks).unwrap();
+
+        assert!(mt.take(1000).eq(clone.take(1000)));
+    }
+}
@@ -0,0 +1,230 @@
+use crate::random::RandomSource;
+use crate::Buffer;
+
+const BLOCK_SIZE: usize = 16;
+
+const SECRETS: [&str; 10] = [
+    "MDAwMDAwTm93IHRoYXQgdGhlIHBhcnR5IGlzIGp1bXBpbmc=",
+    "MDAwMDAxV2l0aCB0aGUgYmFzcyBraWNrZWQgaW4gYW5kIHRoZSBWZWdhJ3MgYXJlIHB1bXBpbic=",
+    "MDAwMDAyUXVpY2sgdG8gdGhlIHBvaW50LCB0byB0aGUgcG9pbnQsIG5vIGZha2luZw==",
+    "MDAwMDAzQ29va2luZyBNQydzIGxpa2UgYSBwb3VuZCBvZiBiYWNvbg==",
+    "MDAwMDA0QnVybmluZyAnZW0sIGlmIHlvdSBhaW4ndCBxdWljayBhbmQgbmltYmxl",
+    "MDAwMDA1SSBnbyBjcmF6eSB3aGVuIEkgaGVhciBhIGN5bWJhbCBhbmQ=",
+    "MDAwMDA2QW5kIGEgaGlnaCBoYXQgd2l0aCBhIHNvdXBlZCB1cCB0ZW1wbw==",
+    "MDAwMDA3SSdtIG9uIGEgcm9sbCwgaXQncyB0aW1lIHRvIGdvIHNvbG8=",
+    "MDAwMDA4b2xsaW4nIGluIG15IGZpdmUgcG9pbnQgb2g=",
+    "MDAwMDA5aXRoIG15IHJhZy10b3AgZG93biBzbyBteSBoYWlyIGNhbiBibG93",
+];
+
+pub trait PaddingOracle {
+    fn padding_valid(&self, iv: &[u8], ciphertext: &[u8]) -> bool;
+}
+
+pub struct PaddingOracleService {
+    key: Vec<u8>,
+    secrets: Vec<Buffer>,
+}
+
+impl PaddingOracleService {
+    pub fn new(random: &mut dyn RandomSource) -> Self {
+        Self {
+            key: random.bytes(BLOCK_SIZE),
+            secrets: SECRETS
+                .iter()
+                .map(|secret| Buffer::from_base64(secret))
+                .collect(),
+        }
+    }
+
+    /// Encrypt one of the secrets at random, returning the IV and ciphertext.
+    pub fn encrypt(&self, random: &mut dyn RandomSource) -> (Buffer, Buffer) {
+        let choice = random.below(self.secrets.len() as u64) as usize;
+        let iv = Buffer::new(&random.bytes(BLOCK_SIZE));
+
+        let mut plaintext = self.secrets[choice].clone();
+        plaintext.pad_to_multiple(BLOCK_SIZE);
+
+        let ciphertext = plaintext.aes_128_cbc_encrypt(&self.key, iv.as_ref());
+
+        (iv, ciphertext)
+    }
+
+    /// The strings `encrypt` picks from, so that tests can check a
+    /// decryption.
+    #[cfg(test)]
+    pub fn secrets(&self) -> &[Buffer] {
+        &self.secrets
+    }
+}
+
+impl PaddingOracle for PaddingOracleService {
+    fn padding_valid(&self, iv: &[u8], ciphertext: &[u8]) -> bool {
+        if ciphertext.is_empty() || !ciphertext.len().is_multiple_of(BLOCK_SIZE) {
+            return false;
+        }
+
+        Buffer::new(ciphertext)
+            .aes_128_cbc_decrypt(&self.key, iv)
+            .has_valid_padding(BLOCK_SIZE)
+    }
+}
+
+/// Recover the block cipher decryption of `block`, before it is XORed with
+/// the previous ciphertext block.
+///
+/// The intermediate bytes are found from the back: with the tail already
+/// known, a forged previous block is chosen so the tail decrypts to `pad`,
+/// and the byte in front of it is guessed until the oracle accepts.
+pub fn intermediate_block<O: PaddingOracle>(oracle: &O, block: &[u8]) -> Vec<u8> {
+    let mut intermediate = vec![0u8; BLOCK_SIZE];
+
+    for pad in 1..=BLOCK_SIZE {
+        let position = BLOCK_SIZE - pad;
+        let mut forged = vec![0u8; BLOCK_SIZE];
+
+        for i in position + 1..BLOCK_SIZE {
+            forged[i] = intermediate[i] ^ pad as u8;
+        }
+
+        let guess = (0..=u8::MAX)
+            .find(|guess| {
+                forged[position] = *guess;
+
+                if !oracle.padding_valid(&forged, block) {
+                    return false;
+                }
+
+                // A lone trailing byte may also have been accepted because
+                // it completed a longer padding such as `\x02\x02`; changing
+                // the byte in front of it tells the two apart.
+                if pad == 1 && position > 0 {
+                    let mut probe = forged.clone();
+                    probe[position - 1] ^= 0xff;
+
+                    return oracle.padding_valid(&probe, block);
+                }
+
+                true
+            })
+            .expect("padding oracle accepted no guess");
+
+        intermediate[position] = guess ^ pad as u8;
+    }
+
+    intermediate
+}
+
+/// Decrypt `ciphertext` using nothing but a padding oracle.
+///
+/// The plaintext is returned with its padding still attached.
+pub fn padding_oracle_decrypt<O: PaddingOracle>(
+    oracle: &O,
+    iv: &Buffer,
+    ciphertext: &Buffer,
+) -> Buffer {
+    assert!(
+        ciphertext.len().is_multiple_of(BLOCK_SIZE),
+        "ciphertext is not a whole number of blocks"
+    );
+
+    let mut previous = iv.as_ref();
+    let mut plaintext = Vec::with_capacity(ciphertext.len());
+
+    for block in ciphertext.as_ref().chunks_exact(BLOCK_SIZE) {
+        let intermediate = Buffer::new(&intermediate_block(oracle, block));
+
+        plaintext.extend(intermediate.xor(previous).as_ref());
+        previous = block;
+    }
+
+    Buffer::new(&plaintext)
+}
+
+/// Encrypt `plaintext` without the key, using nothing but a padding oracle.
+///
+/// Starting from an arbitrary final block, each previous ciphertext block is
+/// chosen as the intermediate of the block after it XORed with the plaintext
+/// it should decrypt to; the last block chosen this way becomes the IV.
+pub fn padding_oracle_encrypt<O: PaddingOracle>(
+    oracle: &O,
+    plaintext: &Buffer,
+    random: &mut dyn RandomSource,
+) -> (Buffer, Buffer) {
+    let mut plaintext = plaintext.clone();
+    plaintext.pad_to_multiple(BLOCK_SIZE);
+
+    let mut blocks = vec![random.bytes(BLOCK_SIZE)];
+
+    for block in plaintext.as_ref().chunks_exact(BLOCK_SIZE).rev() {
+        let next = blocks.last().expect("no ciphertext block");
+        let intermediate = Buffer::new(&intermediate_block(oracle, next));
+
+        blocks.push(intermediate.xor(block).as_ref().to_vec());
+    }
+
+    let iv = Buffer::new(&blocks.pop().expect("no initialization vector"));
+    let ciphertext = Buffer::new(&blocks.into_iter().rev().flatten().collect::<Vec<u8>>());
+
+    (iv, ciphertext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{ChaCha20Rng, TEST_SEED};
+
+    #[test]
+    fn padding_oracle_recovers_every_secret() {
+        let mut random = ChaCha20Rng::seed_from_u64(TEST_SEED);
+        let service = PaddingOracleService::new(&mut random);
+
+        for _ in 0..20 {
+            let (iv, ciphertext) = service.encrypt(&mut random);
+            let mut plaintext = padding_oracle_decrypt(&service, &iv, &ciphertext);
+
+            assert!(plaintext.has_valid_padding(BLOCK_SIZE));
+            plaintext.unpad();
+
+            assert!(service
+                .secrets()
+                .iter()
+                .any(|secret| secret.as_ref() == plaintext.as_ref()));
+        }
+    }
+
+    #[test]
+    fn padding_oracle_forges_chosen_plaintext() {
+        let mut random = ChaCha20Rng::seed_from_u64(TEST_SEED);
+        let service = PaddingOracleService::new(&mut random);
+        let message = Buffer::new(b"Forged without ever seeing the key");
+
+        let (iv, ciphertext) = padding_oracle_encrypt(&service, &message, &mut random);
+        let mut plaintext = ciphertext.aes_128_cbc_decrypt(&service.key, iv.as_ref());
+
+        assert!(plaintext.has_valid_padding(BLOCK_SIZE));
+        plaintext.unpad();
+
+        assert_eq!(plaintext.as_ref(), message.as_ref());
+    }
+
+    #[test]
+    fn trailing_byte_ambiguity_is_resolved() {
+        // With the identity as block cipher the intermediate is the block
+        // itself, and `\x02\x03` makes `\x02\x02` the first accepted guess.
+        struct Identity;
+
+        impl PaddingOracle for Identity {
+            fn padding_valid(&self, iv: &[u8], ciphertext: &[u8]) -> bool {
+                Buffer::new(ciphertext)
+                    .xor(iv)
+                    .has_valid_padding(BLOCK_SIZE)
+            }
+        }
+
+        let mut block = [0u8; BLOCK_SIZE];
+        block[BLOCK_SIZE - 2] = 0x02;
+        block[BLOCK_SIZE - 1] = 0x03;
+
+        assert_eq!(intermediate_block(&Identity, &block), block);
+    }
+}
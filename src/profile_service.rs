@@ -0,0 +1,88 @@
+use crate::key_value::KeyValue;
+use crate::random::RandomSource;
+use crate::Buffer;
+
+const BLOCK_SIZE: usize = 16;
+
+pub struct ProfileService {
+    key: Vec<u8>,
+}
+
+impl ProfileService {
+    pub fn new(random: &mut dyn RandomSource) -> Self {
+        Self {
+            key: random.bytes(BLOCK_SIZE),
+        }
+    }
+
+    pub fn profile_for(&self, email: &str) -> Buffer {
+        let profile = KeyValue::profile_for(email).encode();
+
+        Buffer::new(profile.as_bytes()).aes_128_ecb_encrypt(&self.key)
+    }
+
+    pub fn parse(&self, cookie: &Buffer) -> KeyValue {
+        let mut profile = cookie.aes_128_ecb_decrypt(&self.key);
+        profile.unpad();
+
+        KeyValue::decode(&profile.to_string())
+    }
+}
+
+/// Forge a cookie for `role=admin` using nothing but `profile_for` ciphertexts.
+///
+/// The first request lines up `admin` plus its padding as a block of its own;
+/// the second pushes `role=` to the end of a block so that the block holding
+/// `user` can be swapped for the `admin` block.
+pub fn forge_admin_profile(service: &ProfileService) -> Buffer {
+    let prefix = "email=".len();
+    let to_block_end = |used: usize| (BLOCK_SIZE - used % BLOCK_SIZE) % BLOCK_SIZE;
+
+    let mut admin = Buffer::new(b"admin");
+    admin.pad(BLOCK_SIZE);
+
+    let mut admin_email = "A".repeat(to_block_end(prefix));
+    admin_email.push_str(&admin.to_string());
+
+    let admin_cookie = service.profile_for(&admin_email);
+    let admin_block_start = prefix + to_block_end(prefix);
+    let admin_block = &admin_cookie.as_ref()[admin_block_start..admin_block_start + BLOCK_SIZE];
+
+    let head = prefix + "@bar.com".len() + "&uid=10&role=".len();
+    let user_email = format!("{}@bar.com", "f".repeat(to_block_end(head)));
+
+    let user_cookie = service.profile_for(&user_email);
+    let user_block_start = head + to_block_end(head);
+
+    Buffer::new(
+        &user_cookie.as_ref()[..user_block_start]
+            .iter()
+            .chain(admin_block.iter())
+            .copied()
+            .collect::<Vec<u8>>(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{ChaCha20Rng, TEST_SEED};
+
+    #[test]
+    fn issued_profiles_round_trip() {
+        let service = ProfileService::new(&mut ChaCha20Rng::seed_from_u64(TEST_SEED));
+        let profile = service.parse(&service.profile_for("foo@bar.com"));
+
+        assert_eq!(profile, KeyValue::profile_for("foo@bar.com"));
+        assert_eq!(profile.get("role"), Some("user"));
+    }
+
+    #[test]
+    fn cut_and_paste_forges_admin_role() {
+        let service = ProfileService::new(&mut ChaCha20Rng::seed_from_u64(TEST_SEED));
+        let profile = service.parse(&forge_admin_profile(&service));
+
+        assert_eq!(profile.get("role"), Some("admin"));
+        assert_eq!(profile.get("uid"), Some("10"));
+    }
+}
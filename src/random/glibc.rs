@@ -0,0 +1,162 @@
+use crate::gf2::{BitVector, LinearSystem};
+use std::iter;
+
+const DEGREE: usize = 31;
+const SEPARATION: usize = 3;
+
+/// The default glibc `rand`: an additive lagged Fibonacci generator,
+/// `r[i] = r[i - 3] + r[i - 31]`, outputting all but the lowest bit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlibcRandom {
+    /// The last 31 words, oldest at `index`.
+    words: [u32; DEGREE],
+    index: usize,
+}
+
+impl GlibcRandom {
+    /// Seed like `srand`.
+    pub fn new(seed: u32) -> Self {
+        let seed = if seed == 0 { 1 } else { seed };
+        let mut initial = vec![seed];
+
+        for i in 1..DEGREE {
+            let previous = initial[i - 1] as i32 as i64;
+            initial.push((16807 * previous).rem_euclid(2_147_483_647) as u32);
+        }
+
+        for i in DEGREE..DEGREE + SEPARATION {
+            initial.push(initial[i - DEGREE]);
+        }
+
+        let mut words = [0; DEGREE];
+        words.copy_from_slice(&initial[initial.len() - DEGREE..]);
+
+        let mut random = Self { words, index: 0 };
+
+        // glibc discards the first 310 words.
+        for _ in DEGREE + SEPARATION..344 {
+            random.next_word();
+        }
+
+        random
+    }
+
+    fn next_word(&mut self) -> u32 {
+        let word = self.words[self.index]
+            .wrapping_add(self.words[(self.index + DEGREE - SEPARATION) % DEGREE]);
+
+        self.words[self.index] = word;
+        self.index = (self.index + 1) % DEGREE;
+
+        word
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        self.next_word() >> 1
+    }
+
+    /// Clone a generator from consecutive outputs, returning it positioned
+    /// after the last of them.
+    ///
+    /// Only the lowest bit of each word is hidden. Those bits follow the
+    /// recurrence linearly, and whenever an output exceeds the sum of its two
+    /// predecessors by one, both of their hidden bits must have been set; a
+    /// few hundred outputs give enough such equations to solve for them.
+    pub fn recover(outputs: &[u32]) -> Option<Self> {
+        if outputs.len() < DEGREE {
+            return None;
+        }
+
+        let mut low_bits = (0..DEGREE)
+            .map(|i| BitVector::unit(DEGREE, i))
+            .collect::<Vec<BitVector>>();
+
+        let mut system = LinearSystem::new(DEGREE);
+
+        for i in DEGREE..outputs.len() {
+            let (older, newer) = (i - DEGREE, i - SEPARATION);
+            let carry = outputs[i]
+                .wrapping_sub(outputs[older])
+                .wrapping_sub(outputs[newer])
+                & 0x7fff_ffff;
+
+            match carry {
+                0 => (),
+
+                1 => {
+                    system.add(low_bits[older].clone(), true);
+                    system.add(low_bits[newer].clone(), true);
+                }
+
+                _ => return None,
+            }
+
+            let mut low_bit = low_bits[older].clone();
+            low_bit ^= &low_bits[newer];
+            low_bits.push(low_bit);
+        }
+
+        if system.rank() < DEGREE {
+            return None;
+        }
+
+        let solution = system.solve()?;
+        let tail = outputs.len() - DEGREE;
+
+        let mut words = [0; DEGREE];
+
+        for (i, word) in words.iter_mut().enumerate() {
+            *word = outputs[tail + i] << 1 | low_bits[tail + i].dot(&solution) as u32;
+        }
+
+        let clone = Self { words, index: 0 };
+
+        // Replay from the solved start to check every output.
+        let mut check = Self {
+            words: (0..DEGREE)
+                .map(|i| outputs[i] << 1 | solution.get(i) as u32)
+                .collect::<Vec<u32>>()
+                .try_into()
+                .ok()?,
+            index: 0,
+        };
+
+        outputs[DEGREE..]
+            .iter()
+            .all(|output| check.next_u32() == *output)
+            .then_some(clone)
+    }
+}
+
+impl iter::Iterator for GlibcRandom {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_u32())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glibc_rand_matches_reference() {
+        let outputs: Vec<u32> = GlibcRandom::new(1).take(5).collect();
+
+        assert_eq!(
+            outputs,
+            [1804289383, 846930886, 1681692777, 1714636915, 1957747793]
+        );
+    }
+
+    #[test]
+    fn glibc_rand_is_cloned_from_outputs() {
+        let mut random = GlibcRandom::new(0xdecaf);
+        let outputs: Vec<u32> = (&mut random).take(400).collect();
+
+        let clone = GlibcRandom::recover(&outputs).unwrap();
+
+        assert!(random.take(1000).eq(clone.take(1000)));
+    }
+}
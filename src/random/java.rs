@@ -0,0 +1,339 @@
+use crate::lattice::Lattice;
+use std::iter;
+
+const MULTIPLIER: u64 = 0x5_deec_e66d;
+const INCREMENT: u64 = 0xb;
+const MASK: u64 = (1 << 48) - 1;
+
+/// The most hidden state bits `JavaRandom::recover` searches through.
+const SEARCH_BITS: u32 = 24;
+
+/// `java.util.Random`: a 48-bit LCG whose outputs are the top bits of the
+/// state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JavaRandom {
+    state: u64,
+}
+
+impl JavaRandom {
+    /// Seed like `new Random(seed)`.
+    pub fn new(seed: i64) -> Self {
+        Self {
+            state: (seed as u64 ^ MULTIPLIER) & MASK,
+        }
+    }
+
+    /// A generator with the given internal (already scrambled) state.
+    pub fn from_state(state: u64) -> Self {
+        Self {
+            state: state & MASK,
+        }
+    }
+
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    /// The top `bits` bits of the next state, like the protected `next`.
+    pub fn next(&mut self, bits: u32) -> u32 {
+        assert!((1..=32).contains(&bits), "Random.next takes 1 to 32 bits");
+
+        self.state = step(self.state);
+        (self.state >> (48 - bits)) as u32
+    }
+
+    pub fn next_int(&mut self) -> i32 {
+        self.next(32) as i32
+    }
+
+    /// `nextInt(bound)`, including its rejection of biased draws.
+    pub fn next_int_bounded(&mut self, bound: i32) -> i32 {
+        assert!(bound > 0, "bound must be positive");
+
+        if bound & -bound == bound {
+            return ((bound as i64 * self.next(31) as i64) >> 31) as i32;
+        }
+
+        loop {
+            let bits = self.next(31) as i32;
+            let value = bits % bound;
+
+            if bits.wrapping_sub(value).wrapping_add(bound - 1) >= 0 {
+                return value;
+            }
+        }
+    }
+
+    pub fn next_long(&mut self) -> i64 {
+        let high = self.next(32) as i32 as i64;
+        let low = self.next(32) as i32 as i64;
+
+        (high << 32).wrapping_add(low)
+    }
+
+    pub fn next_float(&mut self) -> f32 {
+        self.next(24) as f32 / (1 << 24) as f32
+    }
+
+    /// Clone a generator from consecutive `next(bits)` outputs, returning it
+    /// positioned after the last of them.
+    ///
+    /// The first output fixes the top `bits` bits of the state. For outputs
+    /// of at least 24 bits the other `2^(48 - bits)` candidates are searched,
+    /// the remaining outputs weeding out the wrong ones; two outputs almost
+    /// always suffice for `nextInt` and three for `nextFloat`.
+    ///
+    /// Shorter outputs hide too much of the state to search, so the hidden
+    /// bits of all the states are found at once instead, as the closest
+    /// vector of a lattice. That takes more outputs, about `48 / bits + 4`,
+    /// and works down to 4-bit ones. With fewer outputs several states may
+    /// fit them, and the one returned can be wrong; `None` means that none
+    /// was found.
+    pub fn recover(bits: u32, outputs: &[u32]) -> Option<Self> {
+        assert!((1..=32).contains(&bits), "Random.next takes 1 to 32 bits");
+
+        let (first, rest) = outputs.split_first()?;
+        let hidden = 48 - bits;
+
+        if hidden <= SEARCH_BITS {
+            let top = (*first as u64) << hidden;
+
+            return (0..1u64 << hidden)
+                .find_map(|low| Self::replay(top | low, rest, |random| random.next(bits)));
+        }
+
+        let state = truncated_state(hidden, outputs)?;
+
+        Self::replay(state, rest, |random| random.next(bits))
+    }
+
+    /// Clone a generator from consecutive `nextInt(bound)` outputs, returning
+    /// it positioned after the last of them.
+    ///
+    /// A power-of-two bound keeps the top bits of the state, as `next` does.
+    /// Any other bound reduces 31 of them modulo `bound`, and the lattice
+    /// then solves for the quotients as well: ten outputs suffice for bounds
+    /// of 100 and up and fewer for larger ones, while smaller bounds give so
+    /// little away that even dozens of outputs may end in `None`. So does a
+    /// draw that `nextInt` rejected and redrew, which skips a state.
+    pub fn recover_from_bounded_ints(bound: i32, outputs: &[i32]) -> Option<Self> {
+        assert!(bound > 0, "bound must be positive");
+
+        if bound == 1 || outputs.iter().any(|output| !(0..bound).contains(output)) {
+            return None;
+        }
+
+        if bound & -bound == bound {
+            return Self::recover(
+                bound.trailing_zeros(),
+                &outputs
+                    .iter()
+                    .map(|output| *output as u32)
+                    .collect::<Vec<u32>>(),
+            );
+        }
+
+        let state = reduced_state(bound, outputs)?;
+
+        Self::replay(state, &outputs[1..], |random| {
+            random.next_int_bounded(bound)
+        })
+    }
+
+    pub fn recover_from_ints(outputs: &[i32]) -> Option<Self> {
+        Self::recover(
+            32,
+            &outputs
+                .iter()
+                .map(|output| *output as u32)
+                .collect::<Vec<u32>>(),
+        )
+    }
+
+    pub fn recover_from_floats(outputs: &[f32]) -> Option<Self> {
+        Self::recover(
+            24,
+            &outputs
+                .iter()
+                .map(|output| (output * (1 << 24) as f32) as u32)
+                .collect::<Vec<u32>>(),
+        )
+    }
+
+    /// The generator in `state`, stepped past `outputs` if it produces them.
+    fn replay<T: PartialEq>(
+        state: u64,
+        outputs: &[T],
+        draw: impl Fn(&mut Self) -> T,
+    ) -> Option<Self> {
+        let mut random = Self::from_state(state);
+
+        outputs
+            .iter()
+            .all(|output| draw(&mut random) == *output)
+            .then_some(random)
+    }
+}
+
+fn step(state: u64) -> u64 {
+    state.wrapping_mul(MULTIPLIER).wrapping_add(INCREMENT) & MASK
+}
+
+/// The multiplier and increment taking the first state of `count`
+/// consecutive ones to each of them.
+fn jumps(count: usize) -> Vec<(u64, u64)> {
+    iter::successors(Some((1u64, 0)), |(multiplier, increment)| {
+        Some((multiplier.wrapping_mul(MULTIPLIER) & MASK, step(*increment)))
+    })
+    .take(count)
+    .collect()
+}
+
+/// The state behind the first of `outputs`, each the top bits of a state
+/// above `hidden` unknown ones.
+///
+/// Writing the states as `(output << hidden) + low` and the jumps as
+/// `multiplier * state + increment`, the lows minus known offsets are a
+/// vector of the lattice spanned by the multipliers and multiples of 2^48.
+/// The lows being small, that is the lattice vector closest to the offsets
+/// subtracted from the middle of their range.
+fn truncated_state(hidden: u32, outputs: &[u32]) -> Option<u64> {
+    let count = outputs.len();
+    let first = (outputs[0] as u64) << hidden;
+
+    let mut basis = vec![vec![0; count]; count];
+    let mut target = Vec::with_capacity(count);
+
+    for (i, ((multiplier, increment), output)) in jumps(count).iter().zip(outputs).enumerate() {
+        basis[0][i] = *multiplier as i128;
+
+        if i > 0 {
+            basis[i][i] = 1 << 48;
+        }
+
+        let offset = multiplier
+            .wrapping_mul(first)
+            .wrapping_add(*increment)
+            .wrapping_sub((*output as u64) << hidden)
+            & MASK;
+
+        target.push((1 << (hidden - 1)) - offset as i128);
+    }
+
+    let low = Lattice::reduce(basis).closest_vector(&target)[0];
+
+    (0..1 << hidden)
+        .contains(&low)
+        .then_some(first | low as u64)
+}
+
+/// The state behind the first of `outputs`, each the top 31 bits of a state
+/// reduced modulo `bound`.
+///
+/// Those bits are `output + bound * quotient`, with the quotient below
+/// `2^31 / bound` and 17 unknown low bits beneath. As in `truncated_state`
+/// the lows come out of a closest lattice vector, which now has a coordinate
+/// for every quotient too; the two kinds are weighted to make their ranges
+/// alike.
+fn reduced_state(bound: i32, outputs: &[i32]) -> Option<u64> {
+    let count = outputs.len();
+    let bound = bound as i128;
+    let unit = 1 << 17;
+    let quotients = (1 << 31) / bound;
+    let low_weight = ((1 << 14) / bound).max(1);
+    let quotient_weight = (bound >> 14).max(1);
+    let first = unit * outputs[0] as i128;
+
+    // The lows come first, then the quotients. Row 0 adds to the first low,
+    // row `count` to the first quotient, the other rows in the first half
+    // wrap a low modulo 2^48 and those in the second half add to a later
+    // quotient.
+    let mut basis = vec![vec![0; 2 * count]; 2 * count];
+    let mut target = vec![quotient_weight * quotients / 2; 2 * count];
+
+    for (i, ((multiplier, increment), output)) in jumps(count).iter().zip(outputs).enumerate() {
+        let multiplier = *multiplier as i128;
+
+        basis[0][i] = low_weight * multiplier;
+
+        if i > 0 {
+            basis[i][i] = low_weight << 48;
+            basis[count][i] = low_weight * (unit * bound * multiplier % (1 << 48));
+            basis[count + i][i] = -low_weight * unit * bound;
+        }
+
+        basis[count + i][count + i] = quotient_weight;
+
+        let offset =
+            (multiplier * first + *increment as i128 - unit * *output as i128).rem_euclid(1 << 48);
+
+        target[i] = low_weight * (unit / 2 - offset);
+    }
+
+    let closest = Lattice::reduce(basis).closest_vector(&target);
+    let low = closest[0] / low_weight;
+    let quotient = closest[count] / quotient_weight;
+
+    ((0..unit).contains(&low) && (0..quotients).contains(&quotient))
+        .then(|| (first + unit * bound * quotient + low) as u64)
+}
+
+impl iter::Iterator for JavaRandom {
+    type Item = i32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_int())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn java_random_matches_reference() {
+        assert_eq!(JavaRandom::new(42).next_int(), -1170105035);
+        assert_eq!(JavaRandom::new(0).next_int(), -1155484576);
+        assert_eq!(JavaRandom::new(42).next_int_bounded(10), 0);
+    }
+
+    #[test]
+    fn java_random_is_cloned_from_ints_and_floats() {
+        let mut random = JavaRandom::new(0x1234_5678_9abc);
+        let ints: Vec<i32> = (&mut random).take(2).collect();
+
+        let mut clone = JavaRandom::recover_from_ints(&ints).unwrap();
+
+        assert_eq!(random.next_long(), clone.next_long());
+
+        let floats: Vec<f32> = (0..3).map(|_| random.next_float()).collect();
+        let mut clone = JavaRandom::recover_from_floats(&floats).unwrap();
+
+        assert!((&mut random).take(100).eq((&mut clone).take(100)));
+    }
+
+    #[test]
+    fn java_random_is_cloned_from_short_outputs() {
+        let mut random = JavaRandom::new(0x1234_5678_9abc);
+        let bytes: Vec<u32> = (0..10).map(|_| random.next(8)).collect();
+        let mut clone = JavaRandom::recover(8, &bytes).unwrap();
+
+        assert!((&mut random).take(100).eq((&mut clone).take(100)));
+        assert_eq!(JavaRandom::recover(8, &[0; 12]), None);
+    }
+
+    #[test]
+    fn java_random_is_cloned_from_bounded_ints() {
+        let mut random = JavaRandom::new(-0x5eed);
+
+        for (bound, count) in [(16, 16), (1000, 8), (1 << 20, 4), (123_456_789, 4)] {
+            let outputs: Vec<i32> = (0..count).map(|_| random.next_int_bounded(bound)).collect();
+            let mut clone = JavaRandom::recover_from_bounded_ints(bound, &outputs).unwrap();
+
+            assert!((&mut random).take(100).eq((&mut clone).take(100)));
+        }
+
+        assert_eq!(JavaRandom::recover_from_bounded_ints(10, &[3, 10]), None);
+        assert_eq!(JavaRandom::recover_from_bounded_ints(1, &[0; 40]), None);
+    }
+}
@@ -0,0 +1,136 @@
+use std::iter;
+
+/// A linear congruential generator modulo a power of two that outputs a
+/// window of bits of its state, like the C library `rand` of many systems.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lcg {
+    pub multiplier: u64,
+    pub increment: u64,
+    pub modulus_bits: u32,
+    pub output_shift: u32,
+    pub output_bits: u32,
+    state: u64,
+}
+
+impl Lcg {
+    /// glibc `rand` when initialised with the smallest `initstate` buffer.
+    pub fn glibc_type0(seed: u32) -> Self {
+        Self::with_parameters(1_103_515_245, 12345, 31, 0, 31, seed as u64)
+    }
+
+    /// The Microsoft C runtime `rand`, outputting bits 16 to 30.
+    pub fn msvc(seed: u32) -> Self {
+        Self::with_parameters(214_013, 2_531_011, 32, 16, 15, seed as u64)
+    }
+
+    pub fn with_parameters(
+        multiplier: u64,
+        increment: u64,
+        modulus_bits: u32,
+        output_shift: u32,
+        output_bits: u32,
+        state: u64,
+    ) -> Self {
+        assert!(modulus_bits <= 48, "modulus too large");
+        assert!(
+            output_shift + output_bits <= modulus_bits,
+            "output window outside the state"
+        );
+
+        let mut lcg = Self {
+            multiplier,
+            increment,
+            modulus_bits,
+            output_shift,
+            output_bits,
+            state: 0,
+        };
+
+        lcg.state = state & lcg.mask();
+        lcg
+    }
+
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    fn mask(&self) -> u64 {
+        (1 << self.modulus_bits) - 1
+    }
+
+    fn output(&self, state: u64) -> u32 {
+        ((state >> self.output_shift) & ((1 << self.output_bits) - 1)) as u32
+    }
+
+    fn step(&self, state: u64) -> u64 {
+        state
+            .wrapping_mul(self.multiplier)
+            .wrapping_add(self.increment)
+            & self.mask()
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        self.state = self.step(self.state);
+        self.output(self.state)
+    }
+
+    /// Clone a generator with the parameters of `self` from consecutive
+    /// outputs, returning it positioned after the last of them.
+    ///
+    /// Every state bit outside the output window is guessed, so this takes
+    /// up to `2^(modulus_bits - output_bits)` trials.
+    pub fn recover(&self, outputs: &[u32]) -> Option<Self> {
+        let (first, rest) = outputs.split_first()?;
+        let hidden_low = self.output_shift;
+        let hidden_high = self.modulus_bits - self.output_shift - self.output_bits;
+
+        assert!(hidden_low + hidden_high <= 32, "too many hidden state bits");
+
+        let top = (*first as u64) << self.output_shift;
+
+        (0..1u64 << (hidden_low + hidden_high))
+            .map(|guess| {
+                let low = guess & ((1 << hidden_low) - 1);
+                let high = guess >> hidden_low;
+
+                top | low | high << (self.output_shift + self.output_bits)
+            })
+            .find_map(|state| {
+                let mut clone = Self { state, ..*self };
+
+                rest.iter()
+                    .all(|output| clone.next_u32() == *output)
+                    .then_some(clone)
+            })
+    }
+}
+
+impl iter::Iterator for Lcg {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_u32())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn msvc_rand_matches_reference() {
+        let outputs: Vec<u32> = Lcg::msvc(1).take(5).collect();
+
+        assert_eq!(outputs, [41, 18467, 6334, 26500, 19169]);
+    }
+
+    #[test]
+    fn msvc_rand_is_cloned_from_a_few_outputs() {
+        let mut lcg = Lcg::msvc(0x5eed_1234);
+        let outputs: Vec<u32> = (&mut lcg).take(4).collect();
+
+        let clone = Lcg::msvc(0).recover(&outputs).unwrap();
+
+        assert!(lcg.take(100).eq(clone.take(100)));
+    }
+}
@@ -0,0 +1,64 @@
+mod chacha;
+mod os;
+
+// Weak generators met in the wild, with attacks cloning them from outputs.
+// They are not `RandomSource`s, and must never become one.
+pub mod glibc;
+pub mod java;
+pub mod lcg;
+pub mod xorshift;
+
+pub use chacha::ChaCha20Rng;
+pub use os::{DevUrandom, GetRandom};
+
+use std::io;
+
+/// The seed of every generator in tests, which must not depend on it.
+#[cfg(test)]
+pub const TEST_SEED: u64 = 0x5eed;
+
+/// A source of random bytes.
+pub trait RandomSource {
+    fn fill(&mut self, buffer: &mut [u8]);
+
+    fn bytes(&mut self, count: usize) -> Vec<u8> {
+        let mut buffer = vec![0; count];
+        self.fill(&mut buffer);
+        buffer
+    }
+
+    fn byte(&mut self) -> u8 {
+        self.bytes(1)[0]
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut buffer = [0; 8];
+        self.fill(&mut buffer);
+        u64::from_le_bytes(buffer)
+    }
+
+    /// A uniform number in `0..bound`.
+    fn below(&mut self, bound: u64) -> u64 {
+        assert!(bound > 0, "empty range");
+
+        // Reject the top partial copy of `0..bound` to avoid modulo bias.
+        let zone = u64::MAX - u64::MAX % bound;
+
+        loop {
+            let candidate = self.next_u64();
+
+            if candidate < zone {
+                return candidate % bound;
+            }
+        }
+    }
+}
+
+/// The operating system's generator: the `getrandom` system call, falling
+/// back to `/dev/urandom` on kernels without it.
+pub fn os() -> io::Result<Box<dyn RandomSource>> {
+    match GetRandom::new() {
+        Ok(source) => Ok(Box::new(source)),
+        Err(_) => Ok(Box::new(DevUrandom::open()?)),
+    }
+}
@@ -0,0 +1,73 @@
+use super::RandomSource;
+use std::fs::File;
+use std::io::{self, Read};
+
+pub struct DevUrandom(File);
+
+impl DevUrandom {
+    pub fn open() -> io::Result<Self> {
+        File::open("/dev/urandom").map(Self)
+    }
+}
+
+impl RandomSource for DevUrandom {
+    fn fill(&mut self, buffer: &mut [u8]) {
+        self.0.read_exact(buffer).expect("cannot read /dev/urandom");
+    }
+}
+
+/// The `getrandom` system call, which needs no file descriptor.
+pub struct GetRandom(());
+
+impl GetRandom {
+    /// Check that the kernel supports the call before handing out a source.
+    pub fn new() -> io::Result<Self> {
+        getrandom(&mut [0; 1])?;
+
+        Ok(Self(()))
+    }
+}
+
+impl RandomSource for GetRandom {
+    fn fill(&mut self, buffer: &mut [u8]) {
+        getrandom(buffer).expect("getrandom failed");
+    }
+}
+
+fn getrandom(buffer: &mut [u8]) -> io::Result<()> {
+    let mut filled = 0;
+
+    while filled < buffer.len() {
+        let rest = &mut buffer[filled..];
+
+        // SAFETY: the pointer and length describe the writable tail of
+        // `buffer`, and the kernel writes at most that many bytes.
+        let written = unsafe { libc::getrandom(rest.as_mut_ptr().cast(), rest.len(), 0) };
+
+        if written < 0 {
+            let error = io::Error::last_os_error();
+
+            if error.kind() != io::ErrorKind::Interrupted {
+                return Err(error);
+            }
+        } else {
+            filled += written as usize;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operating_system_sources_produce_bytes() {
+        let mut urandom = DevUrandom::open().unwrap();
+        let mut getrandom = GetRandom::new().unwrap();
+
+        assert_ne!(urandom.bytes(32), urandom.bytes(32));
+        assert_ne!(getrandom.bytes(32), getrandom.bytes(32));
+    }
+}
@@ -0,0 +1,262 @@
+use crate::gf2::{BitVector, LinearSystem};
+use std::iter;
+
+const UNKNOWNS: usize = 128;
+
+/// xorshift128+ with the (23, 17, 26) shifts used by V8, Firefox and Safari
+/// for `Math.random`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Xorshift128Plus {
+    state: [u64; 2],
+}
+
+impl Xorshift128Plus {
+    pub fn new(state: [u64; 2]) -> Self {
+        assert!(state != [0, 0], "xorshift state must not be all zero");
+
+        Self { state }
+    }
+
+    pub fn state(&self) -> [u64; 2] {
+        self.state
+    }
+
+    fn step(&mut self) {
+        let [mut s1, s0] = self.state;
+
+        s1 ^= s1 << 23;
+        s1 ^= s1 >> 17;
+        s1 ^= s0;
+        s1 ^= s0 >> 26;
+
+        self.state = [s0, s1];
+    }
+
+    /// The sum of the two new state words, as in the reference code.
+    pub fn next_u64(&mut self) -> u64 {
+        self.step();
+        self.state[0].wrapping_add(self.state[1])
+    }
+
+    /// A double in `[0, 1)` made from the top 52 bits of the first state
+    /// word, as V8 does. V8 hands these out in reverse from a cache of 64,
+    /// which callers observing `Math.random` have to undo.
+    pub fn next_f64(&mut self) -> f64 {
+        self.step();
+        f64::from_bits(self.state[0] >> 12 | 0x3ff0_0000_0000_0000) - 1.0
+    }
+
+    /// Clone a generator from consecutive `next_f64` outputs, returning it
+    /// positioned after the last of them. A handful of outputs suffice.
+    pub fn recover_from_f64(outputs: &[f64]) -> Option<Self> {
+        let leaks = outputs
+            .iter()
+            .map(|output| {
+                let mantissa = (output + 1.0).to_bits() & ((1 << 52) - 1);
+                (u64::MAX << 12, mantissa << 12)
+            })
+            .collect::<Vec<(u64, u64)>>();
+
+        recover(Observed::FirstWord, &leaks)
+    }
+
+    /// Clone a generator from consecutive `next_u64` outputs of which only
+    /// the lowest bit is known, as leaked by `next_u64() % 2`. The lowest bit
+    /// of the sum is linear in the state, so 128 outputs are the minimum.
+    pub fn recover_from_low_bits(bits: &[bool]) -> Option<Self> {
+        let leaks = bits
+            .iter()
+            .map(|bit| (1, *bit as u64))
+            .collect::<Vec<(u64, u64)>>();
+
+        recover(Observed::Sum, &leaks)
+    }
+}
+
+impl iter::Iterator for Xorshift128Plus {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_u64())
+    }
+}
+
+/// One 64-bit word as linear functions of the unknown state, bit by bit.
+type Word = Vec<BitVector>;
+
+/// xorshift128+ run over GF(2), tracking each state bit as a linear
+/// combination of the bits of the initial state.
+struct SymbolicXorshift {
+    state: [Word; 2],
+}
+
+impl SymbolicXorshift {
+    fn new() -> Self {
+        let word = |offset: usize| {
+            (0..64)
+                .map(|bit| BitVector::unit(UNKNOWNS, offset + bit))
+                .collect()
+        };
+
+        Self {
+            state: [word(0), word(64)],
+        }
+    }
+
+    fn step(&mut self) {
+        let [s1, s0] = self.state.clone();
+
+        let s1 = shift_xor(&s1, 23);
+        let s1 = shift_xor(&s1, -17);
+        let s1 = xor(&s1, &s0);
+        let s1 = xor(&s1, &shift(&s0, -26));
+
+        self.state = [s0, s1];
+    }
+}
+
+/// `word << shift`, shifting right for negative `shift`.
+fn shift(word: &[BitVector], shift: i32) -> Word {
+    (0..64)
+        .map(|bit| {
+            let source = bit - shift;
+
+            if (0..64).contains(&source) {
+                word[source as usize].clone()
+            } else {
+                BitVector::zeros(UNKNOWNS)
+            }
+        })
+        .collect()
+}
+
+fn xor(one: &[BitVector], two: &[BitVector]) -> Word {
+    one.iter()
+        .zip(two.iter())
+        .map(|(one, two)| {
+            let mut combination = one.clone();
+            combination ^= two;
+            combination
+        })
+        .collect()
+}
+
+fn shift_xor(word: &[BitVector], amount: i32) -> Word {
+    xor(word, &shift(word, amount))
+}
+
+/// The word that leaks after each step.
+#[derive(Debug, Clone, Copy)]
+enum Observed {
+    /// The first state word, which `next_f64` is made from.
+    FirstWord,
+    /// The sum returned by `next_u64`; only its lowest bit is linear.
+    Sum,
+}
+
+impl Observed {
+    fn concrete(self, random: &Xorshift128Plus) -> u64 {
+        match self {
+            Observed::FirstWord => random.state[0],
+            Observed::Sum => random.state[0].wrapping_add(random.state[1]),
+        }
+    }
+
+    fn symbolic(self, model: &SymbolicXorshift) -> Word {
+        match self {
+            Observed::FirstWord => model.state[0].clone(),
+
+            Observed::Sum => {
+                let mut word = vec![BitVector::zeros(UNKNOWNS); 64];
+                word[0] = xor(&model.state[0][..1], &model.state[1][..1]).remove(0);
+                word
+            }
+        }
+    }
+}
+
+/// Solve for the initial state given `(mask, value)` leaks of the observed
+/// word after each step, then replay to check every leak.
+fn recover(observed: Observed, leaks: &[(u64, u64)]) -> Option<Xorshift128Plus> {
+    if let Observed::Sum = observed {
+        assert!(
+            leaks.iter().all(|(mask, _)| mask & !1 == 0),
+            "only the lowest bit of the sum is linear"
+        );
+    }
+
+    let mut model = SymbolicXorshift::new();
+    let mut system = LinearSystem::new(UNKNOWNS);
+
+    for (mask, value) in leaks {
+        if system.rank() == UNKNOWNS {
+            break;
+        }
+
+        model.step();
+
+        for (bit, combination) in observed.symbolic(&model).into_iter().enumerate() {
+            if mask >> bit & 1 == 1 {
+                system.add(combination, value >> bit & 1 == 1);
+            }
+        }
+    }
+
+    if system.rank() < UNKNOWNS {
+        return None;
+    }
+
+    let solution = system.solve()?;
+    let word = |offset: usize| {
+        (0..64).fold(0, |word, bit| {
+            word | (solution.get(offset + bit) as u64) << bit
+        })
+    };
+
+    if word(0) == 0 && word(64) == 0 {
+        return None;
+    }
+
+    let mut clone = Xorshift128Plus::new([word(0), word(64)]);
+    let mut check = clone;
+
+    for _ in leaks {
+        clone.step();
+    }
+
+    leaks
+        .iter()
+        .all(|(mask, value)| {
+            check.step();
+            observed.concrete(&check) & mask == *value
+        })
+        .then_some(clone)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xorshift_is_cloned_from_doubles() {
+        let mut random = Xorshift128Plus::new([0x0123_4567_89ab_cdef, 0xfedc_ba98_7654_3210]);
+        let doubles: Vec<f64> = (0..5).map(|_| random.next_f64()).collect();
+
+        let clone = Xorshift128Plus::recover_from_f64(&doubles).unwrap();
+
+        assert_eq!(clone, random);
+    }
+
+    #[test]
+    fn xorshift_is_cloned_from_low_bits() {
+        let mut random = Xorshift128Plus::new([0xdead_beef, 0xcafe_f00d_0000_0001]);
+        let bits: Vec<bool> = (&mut random)
+            .take(200)
+            .map(|output| output & 1 == 1)
+            .collect();
+
+        let clone = Xorshift128Plus::recover_from_low_bits(&bits).unwrap();
+
+        assert!(random.take(100).eq(clone.take(100)));
+    }
+}
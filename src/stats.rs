@@ -0,0 +1,442 @@
+use std::f64::consts::PI;
+use std::time::{Duration, Instant};
+
+/// Run `f` and time it on the monotonic clock.
+pub fn time<T, F: FnOnce() -> T>(f: F) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+
+    (result, start.elapsed())
+}
+
+/// Time `count` runs of `f`, in seconds.
+pub fn sample<T, F: FnMut() -> T>(count: usize, mut f: F) -> Samples {
+    let mut samples = Samples::new();
+
+    for _ in 0..count {
+        samples.push_duration(time(&mut f).1);
+    }
+
+    samples
+}
+
+/// A set of measurements.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Samples(Vec<f64>);
+
+impl Samples {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn push(&mut self, value: f64) {
+        self.0.push(value);
+    }
+
+    pub fn push_duration(&mut self, duration: Duration) {
+        self.push(duration.as_secs_f64());
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn sorted(&self) -> Vec<f64> {
+        let mut sorted = self.0.clone();
+        sorted.sort_by(f64::total_cmp);
+        sorted
+    }
+
+    /// The arithmetic mean of a non-empty set.
+    pub fn mean(&self) -> f64 {
+        assert!(!self.is_empty(), "mean of no samples");
+
+        self.0.iter().sum::<f64>() / self.len() as f64
+    }
+
+    /// The unbiased sample variance, which takes at least two samples.
+    pub fn variance(&self) -> f64 {
+        assert!(self.len() >= 2, "variance of fewer than two samples");
+
+        let mean = self.mean();
+
+        self.0
+            .iter()
+            .map(|value| (value - mean).powi(2))
+            .sum::<f64>()
+            / (self.len() - 1) as f64
+    }
+
+    /// The `q`-quantile of a non-empty set for `q` in `[0, 1]`,
+    /// interpolating between the closest ranks.
+    pub fn quantile(&self, q: f64) -> f64 {
+        assert!((0.0..=1.0).contains(&q), "quantile {} outside [0, 1]", q);
+        assert!(!self.is_empty(), "quantile of no samples");
+
+        let sorted = self.sorted();
+        let rank = q * (sorted.len() - 1) as f64;
+        let (below, above) = (rank.floor() as usize, rank.ceil() as usize);
+
+        sorted[below] + (sorted[above] - sorted[below]) * (rank - below as f64)
+    }
+
+    pub fn median(&self) -> f64 {
+        self.quantile(0.5)
+    }
+
+    /// The mean after dropping `fraction` of the samples at each end, for
+    /// `fraction` in `[0, 0.5)` so that something is left.
+    pub fn trimmed_mean(&self, fraction: f64) -> f64 {
+        assert!(
+            (0.0..0.5).contains(&fraction),
+            "trimmed fraction {} outside [0, 0.5)",
+            fraction
+        );
+        assert!(!self.is_empty(), "trimmed mean of no samples");
+
+        let sorted = self.sorted();
+        let trim = (sorted.len() as f64 * fraction) as usize;
+        let kept = &sorted[trim..sorted.len() - trim];
+
+        kept.iter().sum::<f64>() / kept.len() as f64
+    }
+
+    /// The samples within Tukey's fences, 1.5 interquartile ranges beyond
+    /// the quartiles; timings mostly have outliers on the slow side, from
+    /// scheduling and interrupts.
+    pub fn without_outliers(&self) -> Self {
+        let (lower, upper) = (self.quantile(0.25), self.quantile(0.75));
+        let fence = 1.5 * (upper - lower);
+
+        Self(
+            self.0
+                .iter()
+                .copied()
+                .filter(|value| (lower - fence..=upper + fence).contains(value))
+                .collect(),
+        )
+    }
+
+    /// The lowest `fraction` of the samples, rounded up, for timings whose
+    /// slow side is mostly noise. `fraction` lies in `(0, 1]`, so a non-empty
+    /// set keeps at least one sample.
+    pub fn lowest(&self, fraction: f64) -> Self {
+        assert!(
+            fraction > 0.0 && fraction <= 1.0,
+            "lowest fraction {} outside (0, 1]",
+            fraction
+        );
+
+        let sorted = self.sorted();
+        let kept = (sorted.len() as f64 * fraction).ceil() as usize;
+
+        Self(sorted[..kept].to_vec())
+    }
+
+    /// The interval holding the true mean with probability `confidence`,
+    /// from Student's t distribution. Takes at least two samples and a
+    /// `confidence` strictly between 0 and 1.
+    pub fn confidence_interval(&self, confidence: f64) -> (f64, f64) {
+        assert!(
+            confidence > 0.0 && confidence < 1.0,
+            "confidence {} outside (0, 1)",
+            confidence
+        );
+        assert!(
+            self.len() >= 2,
+            "confidence interval of fewer than two samples"
+        );
+
+        let degrees_of_freedom = (self.len() - 1) as f64;
+        let t = student_t_quantile(1.0 - (1.0 - confidence) / 2.0, degrees_of_freedom);
+        let margin = t * (self.variance() / self.len() as f64).sqrt();
+
+        (self.mean() - margin, self.mean() + margin)
+    }
+}
+
+impl From<Vec<f64>> for Samples {
+    fn from(values: Vec<f64>) -> Self {
+        Self(values)
+    }
+}
+
+/// The outcome of Welch's t-test on two sets of samples.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Welch {
+    pub t: f64,
+    pub degrees_of_freedom: f64,
+}
+
+impl Welch {
+    /// The probability of a difference at least this large either way if
+    /// the means were equal.
+    pub fn p_value(&self) -> f64 {
+        2.0 * (1.0 - student_t_cdf(self.t.abs(), self.degrees_of_freedom))
+    }
+
+    /// The probability of the first mean exceeding the second by at least
+    /// this much if they were equal.
+    pub fn p_value_greater(&self) -> f64 {
+        1.0 - student_t_cdf(self.t, self.degrees_of_freedom)
+    }
+}
+
+/// Welch's t-test for a difference between the means of `one` and `two`,
+/// which need not share a variance. Each needs at least two samples.
+pub fn welch_t_test(one: &Samples, two: &Samples) -> Welch {
+    assert!(
+        one.len() >= 2 && two.len() >= 2,
+        "Welch's t-test on fewer than two samples"
+    );
+
+    let (n1, n2) = (one.len() as f64, two.len() as f64);
+    let (v1, v2) = (one.variance() / n1, two.variance() / n2);
+
+    // Identical constant samples leave nothing to divide by.
+    let spread = (v1 + v2).max(f64::MIN_POSITIVE);
+
+    Welch {
+        t: (one.mean() - two.mean()) / spread.sqrt(),
+        degrees_of_freedom: spread.powi(2)
+            / (v1.powi(2) / (n1 - 1.0) + v2.powi(2) / (n2 - 1.0)).max(f64::MIN_POSITIVE),
+    }
+}
+
+/// The Lanczos approximation of `ln Γ(x)` for `x > 0`.
+fn ln_gamma(x: f64) -> f64 {
+    const G: f64 = 7.0;
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    if x < 0.5 {
+        return (PI / (PI * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+
+    let x = x - 1.0;
+    let t = x + G + 0.5;
+    let series = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |sum, (i, c)| {
+            sum + c / (x + i as f64 + 1.0)
+        });
+
+    0.5 * (2.0 * PI).ln() + (x + 0.5) * t.ln() - t + series.ln()
+}
+
+/// The regularised incomplete beta function `I_x(a, b)`, by Lentz's
+/// evaluation of its continued fraction.
+fn incomplete_beta(x: f64, a: f64, b: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+
+    if x >= 1.0 {
+        return 1.0;
+    }
+
+    // The fraction converges quickly only below this point.
+    if x > (a + 1.0) / (a + b + 2.0) {
+        return 1.0 - incomplete_beta(1.0 - x, b, a);
+    }
+
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp() / a;
+
+    let tiny = 1e-300;
+    let (mut c, mut d) = (1.0, 0.0);
+    let mut fraction = 0.0;
+
+    for i in 0..400 {
+        let m = (i / 2) as f64;
+
+        let numerator = match i {
+            0 => 1.0,
+            _ if i % 2 == 0 => m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m)),
+            _ => -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0)),
+        };
+
+        d = 1.0 + numerator * d;
+        d = if d.abs() < tiny { tiny } else { d };
+        d = 1.0 / d;
+
+        c = 1.0 + numerator / c;
+        c = if c.abs() < tiny { tiny } else { c };
+
+        let step = c * d;
+        fraction = if i == 0 { step } else { fraction * step };
+
+        if (step - 1.0).abs() < 1e-14 {
+            break;
+        }
+    }
+
+    front * (fraction - 1.0)
+}
+
+/// The cumulative distribution function of Student's t distribution.
+pub fn student_t_cdf(t: f64, degrees_of_freedom: f64) -> f64 {
+    let tail = 0.5
+        * incomplete_beta(
+            degrees_of_freedom / (degrees_of_freedom + t * t),
+            degrees_of_freedom / 2.0,
+            0.5,
+        );
+
+    if t > 0.0 {
+        1.0 - tail
+    } else {
+        tail
+    }
+}
+
+/// The `p`-quantile of Student's t distribution for `p` strictly between 0
+/// and 1, by bisection over a bracket doubled until it holds the quantile.
+pub fn student_t_quantile(p: f64, degrees_of_freedom: f64) -> f64 {
+    assert!(p > 0.0 && p < 1.0, "probability {} outside (0, 1)", p);
+
+    let mut bound = 1.0;
+
+    while student_t_cdf(-bound, degrees_of_freedom) > p
+        || student_t_cdf(bound, degrees_of_freedom) < p
+    {
+        bound *= 2.0;
+    }
+
+    let (mut low, mut high) = (-bound, bound);
+
+    for _ in 0..100 {
+        let middle = (low + high) / 2.0;
+
+        if student_t_cdf(middle, degrees_of_freedom) < p {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+
+    (low + high) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(one: f64, two: f64) -> bool {
+        (one - two).abs() < 1e-3
+    }
+
+    #[test]
+    fn summaries_and_outliers() {
+        let samples = Samples::from(vec![1.0, 2.0, 3.0, 4.0, 100.0]);
+
+        assert_eq!(samples.median(), 3.0);
+        assert_eq!(samples.trimmed_mean(0.2), 3.0);
+        assert_eq!(
+            samples.without_outliers(),
+            Samples::from(vec![1.0, 2.0, 3.0, 4.0])
+        );
+        assert_eq!(samples.without_outliers().median(), 2.5);
+        assert_eq!(samples.lowest(0.5), Samples::from(vec![1.0, 2.0, 3.0]));
+    }
+
+    #[test]
+    fn student_t_matches_tables() {
+        assert!(close(student_t_cdf(0.0, 5.0), 0.5));
+        assert!(close(student_t_quantile(0.975, 10.0), 2.228));
+        assert!(close(student_t_quantile(0.95, 4.0), 2.132));
+        assert!(close(student_t_quantile(0.975, 1000.0), 1.962));
+        // One degree of freedom is the Cauchy distribution, tan(pi (p - 1/2)).
+        assert!((student_t_quantile(0.99995, 1.0) / 6366.2 - 1.0).abs() < 1e-4);
+        assert!((student_t_quantile(0.00005, 1.0) / -6366.2 - 1.0).abs() < 1e-4);
+
+        let samples = Samples::from(vec![2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]);
+        let (low, high) = samples.confidence_interval(0.95);
+
+        // Mean 5 and standard deviation 2.138 over 8 samples, t = 2.365.
+        assert!(close(low, 5.0 - 2.365 * 2.138 / 8f64.sqrt()));
+        assert!(close(high, 5.0 + 2.365 * 2.138 / 8f64.sqrt()));
+
+        let (low, high) = Samples::from(vec![0.0, 2.0]).confidence_interval(0.9999);
+
+        // Mean 1 and standard error 1 over one degree of freedom, t = 6366.2.
+        assert!(((high - 1.0) / 6366.2 - 1.0).abs() < 1e-4);
+        assert!(((1.0 - low) / 6366.2 - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn welch_tells_shifted_samples_apart() {
+        let base = (0..20).map(|i| (i % 5) as f64).collect::<Vec<f64>>();
+        let shifted = base.iter().map(|value| value + 2.0).collect::<Vec<f64>>();
+
+        let same = welch_t_test(&Samples::from(base.clone()), &Samples::from(base.clone()));
+        let different = welch_t_test(&Samples::from(shifted), &Samples::from(base));
+
+        assert!(close(same.p_value(), 1.0));
+        // t = 2 / sqrt(2 * (40 / 19) / 20) over 38 degrees of freedom.
+        assert!(close(different.t, 4.359));
+        assert!(close(different.degrees_of_freedom, 38.0));
+        assert!((4e-5..6e-5).contains(&different.p_value_greater()));
+    }
+
+    #[test]
+    fn single_samples_summarise() {
+        let single = Samples::from(vec![7.0]);
+
+        assert_eq!(single.mean(), 7.0);
+        assert_eq!(single.quantile(0.0), 7.0);
+        assert_eq!(single.trimmed_mean(0.4), 7.0);
+        assert_eq!(single.lowest(0.01), single);
+        assert!(Samples::new().lowest(0.5).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "quantile of no samples")]
+    fn empty_quantile_is_refused() {
+        Samples::new().median();
+    }
+
+    #[test]
+    #[should_panic(expected = "quantile 1.5 outside [0, 1]")]
+    fn quantile_above_one_is_refused() {
+        Samples::from(vec![1.0, 2.0]).quantile(1.5);
+    }
+
+    #[test]
+    #[should_panic(expected = "variance of fewer than two samples")]
+    fn single_sample_variance_is_refused() {
+        Samples::from(vec![1.0]).variance();
+    }
+
+    #[test]
+    #[should_panic(expected = "trimmed fraction 0.5 outside [0, 0.5)")]
+    fn trimming_everything_is_refused() {
+        Samples::from(vec![1.0, 2.0]).trimmed_mean(0.5);
+    }
+
+    #[test]
+    #[should_panic(expected = "lowest fraction 0 outside (0, 1]")]
+    fn keeping_nothing_is_refused() {
+        Samples::from(vec![1.0, 2.0]).lowest(0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Welch's t-test on fewer than two samples")]
+    fn welch_on_a_single_sample_is_refused() {
+        welch_t_test(&Samples::from(vec![1.0]), &Samples::from(vec![1.0, 2.0]));
+    }
+}
@@ -0,0 +1,276 @@
+use crate::stats::{self, Samples};
+use crate::timing_server::SignatureTarget;
+use std::io;
+
+/// How to summarise the response times measured for one guess.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Statistic {
+    Median,
+    /// The mean after dropping this fraction of samples at each end.
+    TrimmedMean(f64),
+    /// A low quantile ignores the delays that noise only ever adds.
+    Quantile(f64),
+}
+
+impl Statistic {
+    fn apply(self, samples: &Samples) -> f64 {
+        match self {
+            Statistic::Median => samples.median(),
+            Statistic::TrimmedMean(fraction) => samples.trimmed_mean(fraction),
+            Statistic::Quantile(q) => samples.quantile(q),
+        }
+    }
+}
+
+/// Recovers a signature one byte at a time from how long a target takes to
+/// reject guesses, when it compares with an early exit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimingAttack {
+    /// Samples taken of every guess for a byte in each screening pass, and
+    /// of every contender before testing it. Raised to at least 4, so that
+    /// the faster half Welch's t-test looks at has two samples.
+    pub samples: usize,
+    /// How many of the slowest guesses are resampled while the slowest one
+    /// does not stand out, kept between 2 and all 256.
+    pub contenders: usize,
+    /// Resampling rounds before screening every guess again.
+    pub max_rounds: usize,
+    /// Screening passes before giving up on any guess standing out.
+    pub max_passes: usize,
+    /// How often to step back and re-measure an earlier byte when no guess
+    /// stands out, a sign that the earlier byte is wrong.
+    pub max_backtracks: usize,
+    pub statistic: Statistic,
+    /// How unlikely the slowest guess standing out by chance must be, by
+    /// Welch's t-test against the runner-up on the faster half of their
+    /// samples.
+    pub significance: f64,
+}
+
+impl Default for TimingAttack {
+    fn default() -> Self {
+        Self {
+            samples: 5,
+            contenders: 8,
+            max_rounds: 20,
+            max_passes: 3,
+            max_backtracks: 10,
+            statistic: Statistic::Quantile(0.1),
+            significance: 0.01,
+        }
+    }
+}
+
+impl TimingAttack {
+    /// Recover a valid `length`-byte signature for `file`, or `None` if the
+    /// timings led astray too often.
+    pub fn recover_signature<T: SignatureTarget + ?Sized>(
+        &self,
+        target: &T,
+        file: &str,
+        length: usize,
+    ) -> io::Result<Option<Vec<u8>>> {
+        assert!(length > 0, "empty signature");
+
+        let mut signature = vec![0; length];
+        let mut backtracks = 0;
+        let mut position = 0;
+
+        while position < length {
+            // The last byte leaks nothing through timing but is checked
+            // outright.
+            let byte = if position == length - 1 {
+                self.completing_byte(target, file, &mut signature)?
+            } else {
+                self.slowest_byte(target, file, &mut signature, position)?
+            };
+
+            match byte {
+                Some(byte) => {
+                    signature[position] = byte;
+                    position += 1;
+                }
+
+                None if position > 0 && backtracks < self.max_backtracks => {
+                    backtracks += 1;
+                    position -= 1;
+                }
+
+                None if position == length - 1 => return Ok(None),
+
+                // Settle for the slowest guess.
+                None => position += 1,
+            }
+        }
+
+        Ok(Some(signature))
+    }
+
+    fn completing_byte<T: SignatureTarget + ?Sized>(
+        &self,
+        target: &T,
+        file: &str,
+        signature: &mut [u8],
+    ) -> io::Result<Option<u8>> {
+        let last = signature.len() - 1;
+
+        for byte in 0..=255 {
+            signature[last] = byte;
+
+            if target.submit(file, signature)? {
+                return Ok(Some(byte));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// The guess for `position` that the target takes longest to reject, or
+    /// `None` if no guess stands out, leaving the slowest one in place.
+    ///
+    /// All guesses are sampled round-robin, so that drift in the timings hits
+    /// them alike, and outliers are dropped before ranking them. The slowest
+    /// few then get fresh samples, since the slowest of many guesses is
+    /// always somewhat slow by chance, until one of them is significantly
+    /// slower than the runner-up. If none is, every guess is screened again,
+    /// adding to its earlier samples.
+    fn slowest_byte<T: SignatureTarget + ?Sized>(
+        &self,
+        target: &T,
+        file: &str,
+        signature: &mut [u8],
+        position: usize,
+    ) -> io::Result<Option<u8>> {
+        let mut measure = |byte: u8, samples: &mut Samples| {
+            signature[position] = byte;
+            let (result, duration) = stats::time(|| target.submit(file, signature));
+            samples.push_duration(duration);
+
+            result.map(|_| ())
+        };
+
+        let samples = self.samples.max(4);
+        let contender_count = self.contenders.clamp(2, 256);
+
+        let mut screening = vec![Samples::new(); 256];
+        let mut slowest = 0;
+
+        for _ in 0..self.max_passes {
+            for _ in 0..samples {
+                for byte in 0..=255 {
+                    measure(byte, &mut screening[byte as usize])?;
+                }
+            }
+
+            let ranked = self.rank(&screening, 0..=255);
+            let contenders = &ranked[..contender_count];
+            let mut fresh = vec![Samples::new(); 256];
+
+            for round in 0..samples + self.max_rounds {
+                for byte in contenders {
+                    measure(*byte, &mut fresh[*byte as usize])?;
+                }
+
+                if round + 1 < samples {
+                    continue;
+                }
+
+                let ranked = self.rank(&fresh, contenders.iter().copied());
+                let welch = stats::welch_t_test(
+                    &fresh[ranked[0] as usize].lowest(0.5),
+                    &fresh[ranked[1] as usize].lowest(0.5),
+                );
+
+                slowest = ranked[0];
+
+                if welch.p_value_greater() < self.significance {
+                    signature[position] = slowest;
+                    return Ok(Some(slowest));
+                }
+            }
+        }
+
+        signature[position] = slowest;
+
+        Ok(None)
+    }
+
+    /// `bytes` from slowest to fastest.
+    fn rank<I: Iterator<Item = u8>>(&self, samples: &[Samples], bytes: I) -> Vec<u8> {
+        let mut ranked = bytes
+            .map(|byte| {
+                let samples = samples[byte as usize].without_outliers();
+                (byte, self.statistic.apply(&samples))
+            })
+            .collect::<Vec<(u8, f64)>>();
+        ranked.sort_by(|one, two| two.1.total_cmp(&one.1));
+
+        ranked.into_iter().map(|(byte, _)| byte).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{ChaCha20Rng, TEST_SEED};
+    use crate::timing_server::{insecure_compare, TimingServer};
+    use std::time::Duration;
+
+    struct LeakyTarget {
+        secret: Vec<u8>,
+        delay: Duration,
+    }
+
+    impl SignatureTarget for LeakyTarget {
+        fn submit(&self, _file: &str, signature: &[u8]) -> io::Result<bool> {
+            Ok(insecure_compare(&self.secret, signature, self.delay))
+        }
+    }
+
+    #[test]
+    fn timing_attack_recovers_short_signature() {
+        let target = LeakyTarget {
+            secret: vec![0xc0, 0xff, 0xee, 0x42],
+            delay: Duration::from_micros(200),
+        };
+
+        let signature = TimingAttack::default()
+            .recover_signature(&target, "foo", 4)
+            .unwrap();
+
+        assert_eq!(signature, Some(target.secret));
+    }
+
+    #[test]
+    fn degenerate_settings_are_clamped() {
+        let target = LeakyTarget {
+            secret: vec![0xc0, 0xff],
+            delay: Duration::from_micros(200),
+        };
+        let attack = TimingAttack {
+            samples: 1,
+            contenders: 1000,
+            ..TimingAttack::default()
+        };
+
+        assert_eq!(
+            attack.recover_signature(&target, "foo", 2).unwrap(),
+            Some(target.secret)
+        );
+    }
+
+    #[test]
+    fn timing_attack_recovers_hmac_from_server() {
+        let server = TimingServer::new(
+            &mut ChaCha20Rng::seed_from_u64(TEST_SEED),
+            Duration::from_micros(50),
+        );
+
+        let signature = TimingAttack::default()
+            .recover_signature(&server, "foo", 20)
+            .unwrap()
+            .unwrap();
+
+        assert!(server.is_valid("foo", &signature));
+    }
+}
@@ -0,0 +1,296 @@
+use crate::hash::sha1::Sha1;
+use crate::mac::Hmac;
+use crate::random::RandomSource;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// Compare byte by byte, sleeping `delay` after each matching byte and
+/// bailing out at the first mismatch, so the time taken leaks the length of
+/// the matching prefix.
+pub fn insecure_compare(one: &[u8], two: &[u8], delay: Duration) -> bool {
+    for (a, b) in one.iter().zip(two) {
+        if a != b {
+            return false;
+        }
+
+        thread::sleep(delay);
+    }
+
+    one.len() == two.len()
+}
+
+/// Percent-encode everything in `text` but unreserved URL characters.
+fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                char::from(byte).to_string()
+            }
+            byte => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Undo `percent_encode`, or `None` on a malformed escape or non-UTF-8
+/// result.
+fn percent_decode(text: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let escape = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(escape, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+
+    String::from_utf8(bytes).ok()
+}
+
+/// Whether a failed accept concerns only the connection being accepted, as
+/// opposed to the listener itself.
+fn is_transient(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::Interrupted
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::ConnectionReset
+    )
+}
+
+/// Something that accepts or rejects an HMAC-SHA1 signature for a file.
+pub trait SignatureTarget {
+    fn submit(&self, file: &str, signature: &[u8]) -> io::Result<bool>;
+}
+
+/// The web application of the timing challenges: it signs file names with
+/// HMAC-SHA1 under a secret key and checks signatures with
+/// `insecure_compare`.
+pub struct TimingServer {
+    key: Vec<u8>,
+    delay: Duration,
+}
+
+impl TimingServer {
+    pub fn new(random: &mut dyn RandomSource, delay: Duration) -> Self {
+        Self {
+            key: random.bytes(16),
+            delay,
+        }
+    }
+
+    fn signature(&self, file: &str) -> Vec<u8> {
+        Hmac::<Sha1>::mac(&self.key, file.as_bytes())
+    }
+
+    /// Whether `signature` is valid for `file`, so that tests can check an
+    /// attack without timing anything.
+    #[cfg(test)]
+    pub fn is_valid(&self, file: &str, signature: &[u8]) -> bool {
+        let mut hmac = Hmac::<Sha1>::new(&self.key);
+        hmac.update(file.as_bytes());
+        hmac.verify(signature)
+    }
+
+    /// The HTTP status for a query string of the form
+    /// `file=foo&signature=46b4ec58...`, with the file name percent-encoded.
+    pub fn handle(&self, query: &str) -> u16 {
+        let mut file = None;
+        let mut signature = None;
+
+        for pair in query.split('&') {
+            match pair.split_once('=') {
+                Some(("file", value)) => file = percent_decode(value),
+                Some(("signature", value)) => signature = hex::decode(value).ok(),
+                _ => (),
+            }
+        }
+
+        match (file, signature) {
+            (Some(file), Some(signature))
+                if insecure_compare(&self.signature(&file), &signature, self.delay) =>
+            {
+                200
+            }
+
+            _ => 500,
+        }
+    }
+
+    /// Answer one HTTP request on `stream`.
+    fn respond(&self, stream: TcpStream) -> io::Result<()> {
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+
+        // Drain the headers; requests carry no body.
+        let mut header = String::new();
+
+        while reader.read_line(&mut header)? > 2 {
+            header.clear();
+        }
+
+        let status = match request_line.split(' ').nth(1) {
+            Some(target) => {
+                let query = target.split_once('?').map(|(_, query)| query);
+                self.handle(query.unwrap_or_default())
+            }
+
+            None => 400,
+        };
+
+        let reason = match status {
+            200 => "OK",
+            400 => "Bad Request",
+            _ => "Internal Server Error",
+        };
+
+        write!(
+            reader.get_mut(),
+            "HTTP/1.1 {} {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            status,
+            reason
+        )
+    }
+
+    /// Serve on `listener` until accepting fails for good, one thread per
+    /// connection. Connections lost while being accepted are skipped.
+    pub fn serve(self: Arc<Self>, listener: TcpListener) -> io::Result<()> {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(error) if is_transient(&error) => continue,
+                Err(error) => return Err(error),
+            };
+            let server = Arc::clone(&self);
+
+            thread::spawn(move || server.respond(stream));
+        }
+
+        Ok(())
+    }
+
+    /// Serve on a free localhost port in the background.
+    pub fn spawn(self: Arc<Self>) -> io::Result<SocketAddr> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let address = listener.local_addr()?;
+
+        thread::spawn(move || self.serve(listener));
+
+        Ok(address)
+    }
+}
+
+/// The server called directly, skipping sockets.
+impl SignatureTarget for TimingServer {
+    fn submit(&self, file: &str, signature: &[u8]) -> io::Result<bool> {
+        let query = format!(
+            "file={}&signature={}",
+            percent_encode(file),
+            hex::encode(signature)
+        );
+
+        Ok(self.handle(&query) == 200)
+    }
+}
+
+/// A client for a `TimingServer` reached over HTTP.
+pub struct HttpTarget {
+    address: SocketAddr,
+}
+
+impl HttpTarget {
+    pub fn new(address: SocketAddr) -> Self {
+        Self { address }
+    }
+}
+
+impl SignatureTarget for HttpTarget {
+    fn submit(&self, file: &str, signature: &[u8]) -> io::Result<bool> {
+        let mut stream = TcpStream::connect(self.address)?;
+        stream.set_nodelay(true)?;
+
+        write!(
+            stream,
+            "GET /test?file={}&signature={} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+            percent_encode(file),
+            hex::encode(signature),
+            self.address
+        )?;
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response)?;
+
+        match response.split(' ').nth(1) {
+            Some("200") => Ok(true),
+            Some(_) => Ok(false),
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "malformed HTTP response",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{ChaCha20Rng, TEST_SEED};
+
+    #[test]
+    fn insecure_compare_stops_at_first_difference() {
+        assert!(insecure_compare(b"abc", b"abc", Duration::ZERO));
+        assert!(!insecure_compare(b"abc", b"abd", Duration::ZERO));
+        assert!(!insecure_compare(b"abc", b"ab", Duration::ZERO));
+    }
+
+    #[test]
+    fn server_checks_signatures_in_process_and_over_http() {
+        let server = Arc::new(TimingServer::new(
+            &mut ChaCha20Rng::seed_from_u64(TEST_SEED),
+            Duration::ZERO,
+        ));
+        let signature = server.signature("foo");
+        let mut forged = signature.clone();
+        forged[19] ^= 1;
+
+        assert!(server.submit("foo", &signature).unwrap());
+        assert!(!server.submit("foo", &forged).unwrap());
+        assert_eq!(server.handle("file=foo"), 500);
+
+        let http = HttpTarget::new(Arc::clone(&server).spawn().unwrap());
+
+        assert!(http.submit("foo", &signature).unwrap());
+        assert!(!http.submit("foo", &forged).unwrap());
+
+        let awkward = "a file&signature=00 100%.txt";
+        let signature = server.signature(awkward);
+
+        assert!(server.submit(awkward, &signature).unwrap());
+        assert!(http.submit(awkward, &signature).unwrap());
+    }
+
+    #[test]
+    fn only_lost_connections_are_skipped() {
+        assert!(is_transient(&io::ErrorKind::ConnectionAborted.into()));
+        assert!(is_transient(&io::ErrorKind::Interrupted.into()));
+        assert!(!is_transient(&io::Error::from_raw_os_error(libc::EMFILE)));
+    }
+
+    #[test]
+    fn file_names_round_trip_through_percent_encoding() {
+        let name = "sp ace&amp=%/ü";
+
+        assert_eq!(percent_encode(name), "sp%20ace%26amp%3D%25%2F%C3%BC");
+        assert_eq!(percent_decode(&percent_encode(name)).as_deref(), Some(name));
+        assert_eq!(percent_decode("bad%2"), None);
+        assert_eq!(percent_decode("bad%zz"), None);
+    }
+}